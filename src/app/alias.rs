@@ -0,0 +1,98 @@
+//! user defined command aliases and macros
+//! `alias today = page 0; cal 00:11 $date` defines a macro of two commands
+//! $date, $month, $year and $time are filled from the clock, other $names are parameters
+use std::collections::BTreeMap;
+
+fn builtin(name: &str, db: &crate::DataBase) -> Option<String> {
+    let now = db.datetime_loc().ok()?;
+    match name {
+        "date" => Some(now.format("%Y/%m/%d").to_string()),
+        "month" => Some(now.format("%Y/%m").to_string()),
+        "year" => Some(now.format("%Y").to_string()),
+        "time" => Some(now.format("%H:%M").to_string()),
+        _ => None,
+    }
+}
+
+fn is_builtin(name: &str) -> bool {
+    matches!(name, "date" | "month" | "year" | "time")
+}
+
+// split a body into literal text and $names
+fn tokens(body: &str) -> Vec<(bool, &str)> {
+    let mut out = vec![];
+    let mut rest = body;
+    while let Some(i) = rest.find('$') {
+        let name = &rest[i + 1..];
+        let len = name.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(name.len());
+        if len == 0 { out.push((false, &rest[..i + 1])); rest = name; continue }
+        out.push((false, &rest[..i]));
+        out.push((true, &name[..len]));
+        rest = &name[len..];
+    }
+    out.push((false, rest));
+    out
+}
+
+// parameters of a body in order of first appearance
+pub fn params(body: &str) -> Vec<&str> {
+    let mut out = vec![];
+    for (var, name) in tokens(body) {
+        if var && !is_builtin(name) && !out.contains(&name) { out.push(name) }
+    }
+    out
+}
+
+// handle `alias name = body` and `unalias name`, none if the line is neither
+pub fn define(aliases: &mut BTreeMap<String, String>, line: &str) -> Option<Result<String, String>> {
+    if let Some(name) = line.strip_prefix("unalias ") {
+        let name = name.trim();
+        return Some(aliases.remove(name).map(|_| format!("removed alias {name}"))
+            .ok_or(format!("alias {name} does not exist")))
+    }
+    let (name, body) = line.strip_prefix("alias ")?.split_once('=')?;
+    let (name, body) = (name.trim(), body.trim());
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Some(Err(String::from("alias name must be one word")))
+    }
+    if body.is_empty() {
+        return Some(Err(format!("alias {name} has an empty body")))
+    }
+    aliases.insert(name.to_string(), body.to_string());
+    Some(Ok(format!("defined alias {name}")))
+}
+
+// the body of the alias a command calls, with its arguments filled in
+// the body may chain commands with ; and &&, none if the command is not an alias
+pub fn expand(aliases: &BTreeMap<String, String>, command: &str, db: &crate::DataBase) -> Result<Option<String>, String> {
+    let words = super::execute::tokenize(command)?;
+    let mut words = words.iter().map(String::as_str);
+    let name = words.next().unwrap_or_default();
+    let Some(body) = aliases.get(name) else { return Ok(None) };
+    let params = params(body);
+    let args = words.collect::<Vec<_>>();
+    if args.len() != params.len() {
+        Err(format!("alias {name} expects {} argument(s): {}", params.len(), params.join(" ")))?
+    }
+    let mut text = String::new();
+    for (var, s) in tokens(body) {
+        match (var, builtin(s, db)) {
+            (false, _) => text.push_str(s),
+            (true, Some(value)) => text.push_str(&value),
+            // arguments are quoted again so that spaces survive
+            (true, None) => text.push_str(&super::execute::quote(args[params.iter().position(|p| *p == s).unwrap()])),
+        }
+    }
+    Ok(Some(text))
+}
+
+// prompt lines for all aliases, with their parameters as placeholders
+pub fn prompts(aliases: &BTreeMap<String, String>) -> String {
+    let mut out = String::new();
+    for (name, body) in aliases.iter() {
+        out.push_str(name);
+        for p in params(body) { out.push_str(" $"); out.push_str(p) }
+        out.push('\n');
+    }
+    out
+}
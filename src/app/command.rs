@@ -1,52 +1,155 @@
 use serde::*;
+use unicode_width::UnicodeWidthStr;
+
+// killed texts kept for yank
+const MAX_KILLS: usize = 16;
+
+// kind of the last edit, consecutive edits of a kind are undone at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Edit {
+    #[default]
+    Other,
+    Insert,
+    Delete,
+    Kill,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Command {
     command: String,
+    // byte offset in command
     xcursor: usize,
+    #[serde(skip)]
+    kills: Vec<String>,
+    // command and cursor before each group of edits
+    #[serde(skip)]
+    undos: Vec<(String, usize)>,
+    #[serde(skip)]
+    last: Edit,
 }
 
 impl Command {
     pub fn new() -> Self {
-        Command { command: String::new(), xcursor: 0 }
+        Command { command: String::new(), xcursor: 0, kills: vec![], undos: vec![], last: Edit::Other }
     }
     pub fn get(&self) -> &str {
         &self.command
     }
-    pub fn get_regex(&self) -> regex::Regex {
-        use regex::*;
-        Regex::new(&(escape(&self.command)
-            .replace(" ", "[^ ]* ") + "[^ ]*")).unwrap()
-    }
     pub fn set(&mut self, command: impl Into<String>) {
+        self.save(Edit::Other);
         self.command = command.into();
         self.xcursor = self.command.len();
     }
+    // screen column of the cursor, wide characters take two columns
     pub fn xcursor(&self) -> u16 {
-        self.xcursor as u16
+        self.command[..self.xcursor].width() as u16
     }
+    // a new line has nothing to undo
     pub fn clear(&mut self) {
         self.command.clear();
         self.xcursor = 0;
+        self.undos.clear();
+        self.last = Edit::Other;
+    }
+    // remember the line before an edit, unless it continues the last one
+    fn save(&mut self, edit: Edit) {
+        if edit == Edit::Other || edit != self.last {
+            self.undos.push((self.command.clone(), self.xcursor));
+        }
+        self.last = edit;
+    }
+    pub fn undo(&mut self) {
+        let Some((command, xcursor)) = self.undos.pop() else { return };
+        self.command = command;
+        self.xcursor = xcursor;
+        self.last = Edit::Other;
     }
     pub fn l(&mut self) {
+        self.last = Edit::Other;
         if self.xcursor == 0 { return }
         self.xcursor = self.command.floor_char_boundary(self.xcursor - 1);
     }
     pub fn r(&mut self) {
+        self.last = Edit::Other;
         if self.xcursor == self.command.len() { return }
         self.xcursor = self.command.ceil_char_boundary(self.xcursor + 1);
     }
+    pub fn home(&mut self) {
+        self.last = Edit::Other;
+        self.xcursor = 0;
+    }
+    pub fn end(&mut self) {
+        self.last = Edit::Other;
+        self.xcursor = self.command.len();
+    }
+    // start of the word before the cursor, words are separated by spaces
+    fn word_start(&self) -> usize {
+        let head = self.command[..self.xcursor].trim_end();
+        head.rfind(char::is_whitespace).map_or(0, |i| i + head[i..].chars().next().unwrap().len_utf8())
+    }
+    // end of the word after the cursor
+    fn word_end(&self) -> usize {
+        let tail = &self.command[self.xcursor..];
+        let skip = tail.len() - tail.trim_start().len();
+        let word = &tail[skip..];
+        self.xcursor + skip + word.find(char::is_whitespace).unwrap_or(word.len())
+    }
+    pub fn word_l(&mut self) {
+        self.last = Edit::Other;
+        self.xcursor = self.word_start();
+    }
+    pub fn word_r(&mut self) {
+        self.last = Edit::Other;
+        self.xcursor = self.word_end();
+    }
     pub fn put(&mut self, c: char) {
+        // a space ends a group of insertions
+        self.save(if c == ' ' { Edit::Other } else { Edit::Insert });
         self.command.insert(self.xcursor, c);
         self.xcursor += c.len_utf8();
     }
     pub fn del(&mut self) {
         if self.xcursor == self.command.len() { return }
+        self.save(Edit::Delete);
         self.command.remove(self.xcursor);
     }
     pub fn bks(&mut self) {
         if self.xcursor == 0 { return }
-        self.l(); self.del();
+        self.save(Edit::Delete);
+        self.xcursor = self.command.floor_char_boundary(self.xcursor - 1);
+        self.command.remove(self.xcursor);
+    }
+    // cut a range into the kill ring, consecutive kills are joined
+    fn kill(&mut self, from: usize, to: usize) {
+        if from == to { return }
+        let joined = self.last == Edit::Kill;
+        self.save(Edit::Kill);
+        let text = self.command.drain(from..to).collect::<String>();
+        match self.kills.last_mut() {
+            Some(last) if joined && from < self.xcursor => last.insert_str(0, &text),
+            Some(last) if joined => last.push_str(&text),
+            _ => self.kills.push(text),
+        }
+        if self.kills.len() > MAX_KILLS { self.kills.remove(0); }
+        self.xcursor = from;
     }
-}
\ No newline at end of file
+    // ctrl-w
+    pub fn kill_word(&mut self) {
+        self.kill(self.word_start(), self.xcursor);
+    }
+    // ctrl-u
+    pub fn kill_head(&mut self) {
+        self.kill(0, self.xcursor);
+    }
+    // ctrl-k
+    pub fn kill_tail(&mut self) {
+        self.kill(self.xcursor, self.command.len());
+    }
+    // ctrl-y, put back the last killed text
+    pub fn yank(&mut self) {
+        let Some(text) = self.kills.last().cloned() else { return };
+        self.save(Edit::Other);
+        self.command.insert_str(self.xcursor, &text);
+        self.xcursor += text.len();
+    }
+}
@@ -0,0 +1,146 @@
+//! fuzzy completion of the command line, ranked by match quality and frecency
+
+// a matched character
+const MATCH: i64 = 16;
+// a matched character right after the previous one
+const CONSECUTIVE: i64 = 8;
+// a matched character at the start of a word
+const BOUNDARY: i64 = 8;
+// skipping characters between two matched ones
+const GAP_START: i64 = 3;
+const GAP_EXTEND: i64 = 1;
+// frecency of a history entry is scaled by this before it adds to the score
+const FRECENCY: f64 = 20.0;
+
+fn boundary(prev: Option<char>, c: char) -> i64 {
+    match prev {
+        None => BOUNDARY + 2,
+        Some(p) if p.is_whitespace() || "/:_-.#".contains(p) => BOUNDARY,
+        Some(p) if p.is_lowercase() && c.is_uppercase() => BOUNDARY / 2,
+        _ => 0,
+    }
+}
+
+// score the query as a subsequence of text, none if it is not one
+// the best alignment is found by dynamic programming, positions are byte offsets of matched characters
+// a lowercase query ignores case
+pub fn score(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let fold = !query.chars().any(char::is_uppercase);
+    let norm = |c: char| if fold { c.to_lowercase().next().unwrap_or(c) } else { c };
+    let q = query.chars().map(norm).collect::<Vec<_>>();
+    if q.is_empty() { return Some((0, vec![])) }
+    let t = text.char_indices().map(|(i, c)| (i, norm(c), c)).collect::<Vec<_>>();
+    // cheap rejection before the quadratic part
+    let mut rest = t.iter();
+    if !q.iter().all(|qc| rest.any(|(_, tc, _)| tc == qc)) { return None }
+    let (n, m) = (t.len(), q.len());
+    let bonus = (0..n).map(|j| boundary(j.checked_sub(1).map(|k| t[k].2), t[j].2)).collect::<Vec<_>>();
+    const NONE: i64 = i64::MIN / 2;
+    // best score with q[..=i] matched and q[i] at t[j], and where q[i-1] was put
+    let mut best = vec![vec![NONE; n]; m];
+    let mut from = vec![vec![usize::MAX; n]; m];
+    for j in 0..n {
+        if t[j].1 == q[0] { best[0][j] = MATCH + 2 * bonus[j] - (j as i64).min(15) }
+    }
+    for i in 1..m {
+        // best predecessor with a gap, already charged for the gap up to j
+        let mut gap = (NONE, usize::MAX);
+        for j in i..n {
+            if j >= 2 && best[i - 1][j - 2] > NONE && best[i - 1][j - 2] - GAP_START > gap.0 {
+                gap = (best[i - 1][j - 2] - GAP_START, j - 2);
+            }
+            if t[j].1 == q[i] {
+                let consecutive = best[i - 1][j - 1] + CONSECUTIVE.max(bonus[j]);
+                let (prev, k) = if consecutive >= gap.0 { (consecutive, j - 1) } else { gap };
+                if prev > NONE / 2 {
+                    best[i][j] = prev + MATCH + bonus[j];
+                    from[i][j] = k;
+                }
+            }
+            gap.0 -= GAP_EXTEND;
+        }
+    }
+    let (mut j, mut total) = (0..n).map(|j| (j, best[m - 1][j])).max_by_key(|&(j, s)| (s, std::cmp::Reverse(j)))?;
+    if total <= NONE / 2 { return None }
+    // shorter candidates win ties
+    total -= (n - m) as i64 / 8;
+    let mut positions = vec![0; m];
+    for i in (0..m).rev() {
+        positions[i] = t[j].0;
+        j = from[i][j];
+    }
+    Some((total, positions))
+}
+
+#[derive(Clone)]
+pub struct Match {
+    // index into the candidate pool
+    pub index: usize,
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+// cached queries are dropped all at once beyond this
+const MAX_CACHED: usize = 256;
+
+// candidates for the command line and those matching the current query
+// matching is incremental: a longer query only rescans what a cached prefix of it matched
+#[derive(Default)]
+pub struct Completion {
+    // candidate text and frecency bonus, history first
+    pool: Vec<(String, i64)>,
+    query: String,
+    matched: Vec<Match>,
+    // matches of queries seen since the candidates changed
+    cache: std::collections::HashMap<String, Vec<Match>>,
+}
+
+impl Completion {
+    // rebuild candidates from history and prompts
+    pub fn reset(&mut self, history: &super::history::History, prompts: &str) {
+        let now = chrono::Utc::now().timestamp();
+        let mut seen = std::collections::HashSet::new();
+        self.pool = history.frecency(now).into_iter()
+            .map(|(s, f)| (s.to_string(), (FRECENCY * (1.0 + f / 100.0).ln()) as i64))
+            .chain(prompts.lines().map(|s| (s.to_string(), 0)))
+            .filter(|(s, _)| seen.insert(s.clone()))
+            .collect();
+        self.cache.clear();
+        self.scan(None);
+    }
+    // match a new query
+    pub fn update(&mut self, query: &str) {
+        if query == self.query { return }
+        self.query = query.to_string();
+        if let Some(matched) = self.cache.get(query) {
+            self.matched = matched.clone();
+            return
+        }
+        // a candidate that does not match a prefix does not match the query either
+        let prefix = query.char_indices().rev().map(|(i, _)| &query[..i])
+            .find_map(|prefix| self.cache.get(prefix));
+        self.scan(prefix.map(|m| m.iter().map(|m| m.index).collect()));
+    }
+    // scan some candidates, all if none
+    fn scan(&mut self, within: Option<Vec<usize>>) {
+        let within = within.unwrap_or_else(|| (0..self.pool.len()).collect());
+        self.matched = within.into_iter().filter_map(|index| {
+            let (text, bonus) = &self.pool[index];
+            let (score, positions) = score(&self.query, text)?;
+            Some(Match { index, score: score + bonus, positions })
+        }).collect();
+        // stable, equal scores keep history and prompts order
+        self.matched.sort_by_key(|m| std::cmp::Reverse(m.score));
+        if self.cache.len() >= MAX_CACHED { self.cache.clear() }
+        self.cache.insert(self.query.clone(), self.matched.clone());
+    }
+    pub fn len(&self) -> usize {
+        self.matched.len()
+    }
+    pub fn get(&self, i: usize) -> Option<&str> {
+        self.matched.get(i).map(|m| self.pool[m.index].0.as_str())
+    }
+    pub fn positions(&self, i: usize) -> &[usize] {
+        &self.matched[i].positions
+    }
+}
@@ -4,58 +4,563 @@ pub trait TryExecute<App> {
 
 pub enum ArgPattern {
     Word(regex::Regex),
-    Variable(regex::Regex),
+    Variable(regex::Regex, Slot),
+    // may be left out, then the default is used if there is one
+    Optional(regex::Regex, Slot, Option<&'static str>),
+    // one or more arguments
+    Repeated(regex::Regex, Slot),
+    // --name=value anywhere in the command, then the default is used if there is one
+    Keyword(&'static str, regex::Regex, Slot, Option<&'static str>),
 }
 
-pub struct CommandExecution<App> {
+// what kind of value a variable takes, used to fill prompts with real data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    // free text, never filled
+    Text,
+    // one of the alternatives of the regex
+    Choice,
+    Project,
+    Event,
+    // free grid corners of the current page
+    Corner,
+    // grid layout like 22
+    Grid,
+    Page,
+    Date,
+    Color,
+}
+
+// split a command line into arguments
+// "double quotes" allow escapes like \" and \n, 'single quotes' are literal
+// a backslash outside of quotes escapes the next character
+pub fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut out = vec![];
+    let mut token = None::<String>;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => out.extend(token.take()),
+            '\\' => token.get_or_insert_with(String::new)
+                .push(chars.next().ok_or("a backslash ends the command")?),
+            '\'' => {
+                let token = token.get_or_insert_with(String::new);
+                loop { match chars.next() {
+                    Some('\'') => break,
+                    Some(c) => token.push(c),
+                    None => Err("unterminated single quote")?,
+                } }
+            }
+            '"' => {
+                let token = token.get_or_insert_with(String::new);
+                loop { match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some('n') => token.push('\n'),
+                        Some('t') => token.push('\t'),
+                        Some(c) => token.push(c),
+                        None => Err("unterminated double quote")?,
+                    },
+                    Some(c) => token.push(c),
+                    None => Err("unterminated double quote")?,
+                } }
+            }
+            c => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+    out.extend(token);
+    Ok(out)
+}
+
+// split a command line into commands separated by ; and &&
+// each command comes with whether it only runs if the previous one succeeded
+pub fn chain(line: &str) -> Result<Vec<(bool, String)>, String> {
+    let mut out = vec![];
+    let mut command = String::new();
+    let mut and = false;
+    let mut quote = None;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            // escapes are kept for tokenize
+            (Some('\''), _) => {}
+            (_, '\\') => { command.push(c); command.extend(chars.next()); continue }
+            (None, ';') | (None, '&') if c == ';' || chars.peek() == Some(&'&') => {
+                if c == '&' { chars.next(); }
+                if command.trim().is_empty() {
+                    // a trailing or doubled ; is harmless, && needs something on both sides
+                    if and || c == '&' { Err("&& needs a command on both sides")? }
+                } else {
+                    out.push((and, command.trim().to_string()));
+                }
+                command.clear();
+                and = c == '&';
+                continue
+            }
+            _ => {}
+        }
+        command.push(c);
+    }
+    if quote.is_some() { Err("unterminated quote")? }
+    if !command.trim().is_empty() {
+        out.push((and, command.trim().to_string()));
+    } else if and {
+        Err("&& needs a command on both sides")?
+    }
+    Ok(out)
+}
+
+// alternatives of a plain word regex like ^cal|calender$ or ^new$|^\+$
+pub fn alternatives(regex: &regex::Regex) -> Vec<String> {
+    regex.as_str().split('|').filter_map(|alt| {
+        let alt = alt.trim_start_matches('^').trim_end_matches('$');
+        let mut out = String::new();
+        let mut chars = alt.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => out.push(chars.next()?),
+                '[' | ']' | '(' | ')' | '{' | '}' | '*' | '+' | '?' | '.' | '^' | '$' => None?,
+                c => out.push(c),
+            }
+        }
+        (!out.is_empty() && regex.is_match(&out)).then_some(out)
+    }).collect()
+}
+
+// quote an argument so that tokenize gives it back
+pub fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "\"'\\;&".contains(c)) {
+        return arg.to_string()
+    }
+    let mut out = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '\\' => { out.push('\\'); out.push(c) }
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// arguments bound to the variables of a pattern, in declaration order
+// words are not included
+pub struct Args<'a> {
+    // bound values, none for an omitted optional argument without default
+    values: Vec<Vec<&'a str>>,
+    // position of each variable in the command for error messages, none if it is defaulted
+    position: Vec<Option<usize>>,
+}
+
+impl<'a> Args<'a> {
+    // the value of an optional argument
+    pub fn get(&self, i: usize) -> Option<&'a str> {
+        self.values[i].first().copied()
+    }
+    // all values of a repeated argument
+    pub fn all(&self, i: usize) -> &[&'a str] {
+        &self.values[i]
+    }
+    // an error message that names the i-th variable
+    pub fn err(&self, i: usize, msg: impl std::fmt::Display) -> String {
+        match (self.position[i], self.get(i)) {
+            (Some(p), Some(v)) => format!("argument {} `{v}`: {msg}", p + 1),
+            (_, Some(v)) => format!("default value `{v}`: {msg}"),
+            (_, None) => format!("missing argument: {msg}"),
+        }
+    }
+    // parse the i-th variable
+    pub fn parse<T: std::str::FromStr>(&self, i: usize) -> Result<T, String> where T::Err: std::fmt::Display {
+        self.parse_with(i, |s| s.parse::<T>())
+    }
+    // parse the i-th variable with a custom parser
+    pub fn parse_with<T, E: std::fmt::Display>(&self, i: usize, f: impl FnOnce(&'a str) -> Result<T, E>) -> Result<T, String> {
+        let value = self.get(i).ok_or_else(|| self.err(i, "no value is given"))?;
+        f(value).map_err(|e| self.err(i, e))
+    }
+}
+
+impl<'a> std::ops::Index<usize> for Args<'a> {
+    type Output = &'a str;
+    fn index(&self, i: usize) -> &Self::Output {
+        &self.values[i][0]
+    }
+}
+
+// what a command looks like, shown by help and used to generate prompts
+pub struct CommandInfo {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub examples: Vec<&'static str>,
     pub pattern: Vec<ArgPattern>,
-    pub execute: fn(&mut App, Vec<&str>, &mut crate::DataBase) -> Result<(), String>,
+    // short name and description of each pattern, empty for words and undescribed variables
+    pub about: Vec<(&'static str, &'static str)>,
+}
+
+impl CommandInfo {
+    // short name of the i-th pattern, like <project>
+    fn arg_name(&self, i: usize) -> String {
+        let slot = match &self.pattern[i] {
+            ArgPattern::Word(_) => return String::new(),
+            ArgPattern::Variable(_, s) | ArgPattern::Optional(_, s, _)
+            | ArgPattern::Repeated(_, s) | ArgPattern::Keyword(_, _, s, _) => *s,
+        };
+        match self.about[i].0 {
+            "" => format!("{slot:?}").to_lowercase(),
+            name => name.to_string(),
+        }
+    }
+    // one piece of syntax for each pattern, like [--depth=<n>]
+    pub fn syntax_of(&self, i: usize) -> String {
+        let name = self.arg_name(i);
+        match &self.pattern[i] {
+            ArgPattern::Word(r) => alternatives(r).into_iter().next().unwrap_or(r.as_str().to_string()),
+            ArgPattern::Variable(..) => format!("<{name}>"),
+            ArgPattern::Optional(..) => format!("[<{name}>]"),
+            ArgPattern::Repeated(..) => format!("<{name}>..."),
+            ArgPattern::Keyword(key, ..) => format!("[--{key}=<{name}>]"),
+        }
+    }
+    // default value of the i-th pattern
+    pub fn default_of(&self, i: usize) -> Option<&'static str> {
+        match &self.pattern[i] {
+            ArgPattern::Optional(_, _, d) | ArgPattern::Keyword(_, _, _, d) => *d,
+            _ => None,
+        }
+    }
+    // the whole syntax, like report <format> <file> [--depth=<n>]
+    pub fn syntax(&self) -> String {
+        (0..self.pattern.len()).map(|i| self.syntax_of(i)).collect::<Vec<_>>().join(" ")
+    }
+    // regex of the i-th pattern
+    pub fn regex_of(&self, i: usize) -> &str {
+        match &self.pattern[i] {
+            ArgPattern::Word(r) | ArgPattern::Variable(r, _) | ArgPattern::Optional(r, ..)
+            | ArgPattern::Repeated(r, _) | ArgPattern::Keyword(_, r, ..) => r.as_str(),
+        }
+    }
+}
+
+pub struct CommandExecution<App> {
+    pub info: CommandInfo,
+    pub execute: fn(&mut App, Args, &mut crate::DataBase) -> Result<(), String>,
+}
+
+// why a command does not fit a pattern
+enum Miss {
+    // the words or the number of arguments differ
+    Shape,
+    // the shape fits but an argument is malformed
+    Arg(String),
+}
+
+impl<App> CommandExecution<App> {
+    // bind the arguments of a command to the variables of the pattern
+    fn bind<'a>(&self, command: &[&'a str]) -> Result<Args<'a>, Miss> {
+        // keyword arguments are picked out first, positional ones are matched in order
+        let mut keyword = vec![];
+        let mut positional = vec![];
+        for (i, arg) in command.iter().enumerate() {
+            let key = arg.strip_prefix("--").and_then(|a| a.split_once('='));
+            let found = key.and_then(|(k, v)| self.info.pattern.iter().position(
+                |p| matches!(p, ArgPattern::Keyword(name, ..) if *name == k)).map(|p| (p, i, v)));
+            match found {
+                Some(found) => keyword.push(found),
+                None => positional.push((i, *arg)),
+            }
+        }
+        let (mut bound, mut first_miss) = walk(&self.info.pattern, &positional).ok_or(Miss::Shape)?;
+        let mut values = vec![];
+        let mut position = vec![];
+        for (p, pattern) in self.info.pattern.iter().enumerate() {
+            let (regex, default) = match pattern {
+                ArgPattern::Word(_) => continue,
+                ArgPattern::Variable(..) | ArgPattern::Repeated(..) => {
+                    position.push(bound[p].first().map(|&(i, _)| i));
+                    values.push(bound[p].iter().map(|&(_, v)| v).collect());
+                    continue
+                }
+                ArgPattern::Optional(r, _, d) => {
+                    position.push(bound[p].first().map(|&(i, _)| i));
+                    (r, d)
+                }
+                ArgPattern::Keyword(name, r, _, d) => {
+                    let mut given = keyword.iter().filter(|&&(k, ..)| k == p);
+                    let given = match (given.next(), given.next()) {
+                        (Some(_), Some(_)) => Err(Miss::Arg(format!("--{name} is given more than once")))?,
+                        (given, _) => given,
+                    };
+                    if let Some(&(_, i, v)) = given {
+                        if !r.is_match(v) && first_miss.is_none() {
+                            first_miss = Some(format!("argument {} `{v}`: --{name} does not match {}", i + 1, r.as_str()));
+                        }
+                        bound[p] = vec![(i, v)];
+                    }
+                    position.push(given.map(|&(_, i, _)| i));
+                    (r, d)
+                }
+            };
+            match (bound[p].first(), default) {
+                (Some(&(_, v)), _) => values.push(vec![v]),
+                (None, Some(d)) => {
+                    debug_assert!(regex.is_match(d), "default {d} does not match {}", regex.as_str());
+                    values.push(vec![*d])
+                }
+                (None, None) => values.push(vec![]),
+            }
+        }
+        match first_miss {
+            Some(e) => Err(Miss::Arg(e)),
+            None => Ok(Args { values, position }),
+        }
+    }
+}
+
+// bindings of each pattern and the first malformed argument, none if the shape does not fit
+type Walk<'a> = Option<(Vec<Vec<(usize, &'a str)>>, Option<String>)>;
+
+// match positional arguments against patterns, backtracking over optional and repeated ones
+// a malformed argument does not stop matching, so only commands of the right shape report it
+fn walk<'a>(pattern: &[ArgPattern], command: &[(usize, &'a str)]) -> Walk<'a> {
+    use ArgPattern::*;
+    let Some((first, rest)) = pattern.split_first() else {
+        return command.is_empty().then(|| (vec![], None))
+    };
+    // numbers of arguments this pattern may take, in order of preference
+    let (regex, takes) = match first {
+        Keyword(..) => (None, vec![0]),
+        Word(r) => match command.first() {
+            Some((_, v)) if r.is_match(v) => (None, vec![1]),
+            _ => None?,
+        },
+        Variable(r, _) => (Some(r), vec![1]),
+        Optional(r, ..) => (Some(r), vec![1, 0]),
+        Repeated(r, _) => (Some(r), (1..=command.len()).rev().collect()),
+    };
+    let mut best = None;
+    for n in takes.into_iter().filter(|&n| n <= command.len()) {
+        let Some((mut bound, miss)) = walk(rest, &command[n..]) else { continue };
+        let own = regex.and_then(|r| command[..n].iter().find(|(_, v)| !r.is_match(v))
+            .map(|(i, v)| format!("argument {} `{v}` does not match {}", i + 1, r.as_str())));
+        bound.insert(0, command[..n].to_vec());
+        match own.or(miss) {
+            None => return Some((bound, None)),
+            miss => if best.is_none() { best = Some((bound, miss)) },
+        }
+    }
+    best
 }
 
 impl<App> TryExecute<App> for CommandExecution<App> {
     fn try_execute(&self, command: &Vec<&str>, app: &mut App, db: &mut crate::DataBase) -> Result<bool, String> {
-        let will_execute = command.len() == self.pattern.len() && (0..command.len()).map(|i| 
-            match self.pattern[i] {
-                ArgPattern::Word(ref r) => r.is_match(command[i]),
-                ArgPattern::Variable(ref r) => r.is_match(command[i])
-            }
-        ).fold(true, |x, y| x && y);
-        if !will_execute { return Ok(false) }
-        let args = command.iter().enumerate().filter_map(|(i, x)| matches!(self.pattern[i], ArgPattern::Variable(_)).then_some(*x));
-        (self.execute)(app, args.collect(), db).map(|()| true)
+        match self.bind(command) {
+            Ok(args) => (self.execute)(app, args, db).map(|()| true),
+            Err(Miss::Shape) => Ok(false),
+            Err(Miss::Arg(e)) => Err(e),
+        }
+    }
+}
+
+// descriptions of every command, used to generate prompts and help
+pub trait Grammar {
+    fn commands(&self) -> Vec<&CommandInfo>;
+}
+
+impl<App> Grammar for Vec<CommandExecution<App>> {
+    fn commands(&self) -> Vec<&CommandInfo> {
+        self.iter().map(|x| &x.info).collect()
     }
 }
 
 impl<App> TryExecute<App> for Vec<CommandExecution<App>> {
     fn try_execute(&self, command: &Vec<&str>, app: &mut App, db: &mut crate::DataBase) -> Result<bool, String> {
+        // a command that fits one pattern well is not an error of another pattern
+        let mut malformed = None;
         for executors in self.iter() {
-            match executors.try_execute(command, app, db) {
-                Ok(false) => continue,
-                Ok(true) => return Ok(true),
-                Err(e) => return Err(e),
+            match executors.bind(command) {
+                Ok(args) => return (executors.execute)(app, args, db).map(|()| true),
+                Err(Miss::Arg(e)) => { malformed.get_or_insert(e); }
+                Err(Miss::Shape) => continue,
             }
         }
-        Ok(false)
+        malformed.map_or(Ok(false), Err)
     }
 }
 
+// ("name", "summary", ["example", ...], patterns..., |this, args, db| body), examples can be left out
+// w "re": a word, v "re": a variable, o "re" = "default": an optional variable
+// m "re": one or more variables, k "name" "re" = "default": a keyword variable --name=value
+// variables take a slot like v "re" as Project, defaults can be left out
+// variables are described like v "re" : "file" "where the report is written"
 #[macro_export]
 macro_rules! x_decl {
-    ($($x:ident $y:literal, )* |$z0: ident, $z1: ident, $z2: ident| $body: tt) => {
+    ($name:literal, $summary:literal, $([$($e:literal),* $(,)?],)?
+        $($x:ident $y:literal $($y2:literal)? $(as $s:ident)? $(= $d:literal)? $(: $an:literal $ad:literal)?, )*
+        |$z0: ident, $z1: ident, $z2: ident| $body: tt) => {
         crate::app::execute::CommandExecution {
-            pattern: vec![$(crate::app::execute::x_decl!{$x $y}, )*],
+            info: crate::app::execute::CommandInfo {
+                name: $name,
+                summary: $summary,
+                examples: vec![$($($e),*)?],
+                pattern: vec![$(crate::app::execute::x_decl!{$x $y $($y2)? $(as $s)? $(= $d)?}, )*],
+                about: vec![$(crate::app::execute::x_decl!{@about $($an $ad)?}, )*],
+            },
             execute: |$z0, $z1, $z2| $body,
         }
     };
     ($(($($x:tt)*))*) => {
         vec![ $(crate::app::execute::x_decl!{$($x)*}, )* ]
     };
+    (@about) => { ("", "") };
+    (@about $an:literal $ad:literal) => { ($an, $ad) };
+    (@slot) => { crate::app::execute::Slot::Text };
+    (@slot $s:ident) => { crate::app::execute::Slot::$s };
+    (@default) => { None };
+    (@default $d:literal) => { Some($d) };
     (w $x:literal) => {
         crate::app::execute::ArgPattern::Word(regex::Regex::new($x).unwrap())
     };
-    (v $x:literal) => {
-        crate::app::execute::ArgPattern::Variable(regex::Regex::new($x).unwrap())
+    (v $x:literal $(as $s:ident)?) => {
+        crate::app::execute::ArgPattern::Variable(regex::Regex::new($x).unwrap(), crate::app::execute::x_decl!{@slot $($s)?})
+    };
+    (o $x:literal $(as $s:ident)? $(= $d:literal)?) => {
+        crate::app::execute::ArgPattern::Optional(regex::Regex::new($x).unwrap(),
+            crate::app::execute::x_decl!{@slot $($s)?}, crate::app::execute::x_decl!{@default $($d)?})
+    };
+    (m $x:literal $(as $s:ident)?) => {
+        crate::app::execute::ArgPattern::Repeated(regex::Regex::new($x).unwrap(), crate::app::execute::x_decl!{@slot $($s)?})
+    };
+    (k $n:literal $x:literal $(as $s:ident)? $(= $d:literal)?) => {
+        crate::app::execute::ArgPattern::Keyword($n, regex::Regex::new($x).unwrap(),
+            crate::app::execute::x_decl!{@slot $($s)?}, crate::app::execute::x_decl!{@default $($d)?})
     };
 }
 
-pub(crate) use x_decl;
\ No newline at end of file
+pub(crate) use x_decl;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        tokenize(line).unwrap()
+    }
+
+    fn bind<'a>(x: &CommandExecution<()>, line: &'a str) -> Result<Args<'a>, Miss> {
+        x.bind(&line.split(' ').collect::<Vec<_>>())
+    }
+
+    fn arg_err(res: Result<Args, Miss>) -> String {
+        match res {
+            Err(Miss::Arg(e)) => e,
+            Err(Miss::Shape) => panic!("the shape does not fit"),
+            Ok(_) => panic!("the command is bound"),
+        }
+    }
+
+    #[test]
+    fn tokenize_quotes_and_escapes() {
+        assert_eq!(words(r#"ed 00:11 pj "house move""#), ["ed", "00:11", "pj", "house move"]);
+        assert_eq!(words(r#"'a "b' c\ d "x\"y\n""#), ["a \"b", "c d", "x\"y\n"]);
+        // quotes join what touches them and an empty pair is an argument
+        assert_eq!(words(r#"ab"c d"e '' "#), ["abc de", ""]);
+        assert_eq!(words(r"'no \n escape'"), [r"no \n escape"]);
+        assert!(words("  ").is_empty());
+    }
+
+    #[test]
+    fn tokenize_unterminated() {
+        assert_eq!(tokenize(r#"pj new "house"#), Err(String::from("unterminated double quote")));
+        assert_eq!(tokenize(r#"pj new "house\"#), Err(String::from("unterminated double quote")));
+        assert_eq!(tokenize("pj new 'house"), Err(String::from("unterminated single quote")));
+        assert_eq!(tokenize(r"pj new house\"), Err(String::from("a backslash ends the command")));
+    }
+
+    #[test]
+    fn chain_splits_on_separators_outside_quotes() {
+        let chain = |line| chain(line).unwrap();
+        assert_eq!(chain("a; b && c"), [(false, "a".into()), (false, "b".into()), (true, "c".into())]);
+        assert_eq!(chain(r#"ev new "a;b" && x 'c && d'"#), [(false, r#"ev new "a;b""#.into()), (true, "x 'c && d'".into())]);
+        assert_eq!(chain(r"a\;b & c"), [(false, r"a\;b & c".into())]);
+        assert_eq!(chain("a;; b;"), [(false, "a".into()), (false, "b".into())]);
+        assert!(super::chain("&& a").is_err());
+        assert!(super::chain("a &&").is_err());
+        assert!(super::chain("a && ; b").is_err());
+        assert!(super::chain("a \"b; c").is_err());
+    }
+
+    #[test]
+    fn quote_round_trips() {
+        for arg in ["plain", "house move", "", "a\"b\\c", "x;y", "tab\tand\nline"] {
+            assert_eq!(words(&quote(arg)), [arg]);
+        }
+    }
+
+    #[test]
+    fn optional_and_repeated_arguments() {
+        let x: CommandExecution<()> = x_decl! {"plan accept", "",
+            w "^plan$", o r"^[0-3]{2}:[0-3]{2}$" as Corner, m r"^\d+$|^all$", |_this, _args, _db| { Ok(()) }};
+        let args = bind(&x, "plan 1 2 3").ok().unwrap();
+        assert_eq!(args.get(0), None);
+        assert_eq!(args.all(1), ["1", "2", "3"]);
+        let args = bind(&x, "plan 00:11 all").ok().unwrap();
+        assert_eq!(args.get(0), Some("00:11"));
+        assert_eq!(args.all(1), ["all"]);
+        // the optional argument is given back when it would leave nothing to repeat
+        assert_eq!(bind(&x, "plan 12").ok().unwrap().all(1), ["12"]);
+        assert!(matches!(bind(&x, "plan 00:11"), Err(Miss::Arg(_))));
+        assert_eq!(arg_err(bind(&x, "plan 00:11 1 x")), "argument 4 `x` does not match ^\\d+$|^all$");
+    }
+
+    #[test]
+    fn defaults_and_keywords() {
+        let x: CommandExecution<()> = x_decl! {"list", "",
+            w "^list$", o "^.+$" as Project = "root", k "sort" "^name$|^due$" as Choice = "name",
+            k "depth" r"^\d+$", |_this, _args, _db| { Ok(()) }};
+        let args = bind(&x, "list").ok().unwrap();
+        assert_eq!((args.get(0), args.get(1), args.get(2)), (Some("root"), Some("name"), None));
+        assert_eq!(args.err(0, "no project"), "default value `root`: no project");
+        assert_eq!(args.err(2, "is needed"), "missing argument: is needed");
+        // keywords go anywhere and do not take positions
+        let args = bind(&x, "list --sort=due work --depth=2").ok().unwrap();
+        assert_eq!((args.get(0), args.get(1), args.get(2)), (Some("work"), Some("due"), Some("2")));
+        assert_eq!(args.parse::<usize>(2), Ok(2));
+        assert_eq!(args.err(1, "bad"), "argument 2 `due`: bad");
+        assert_eq!(arg_err(bind(&x, "list --sort=size")), "argument 2 `size`: --sort does not match ^name$|^due$");
+        assert_eq!(arg_err(bind(&x, "list --sort=due --sort=name")), "--sort is given more than once");
+        // an unknown key is a positional argument
+        assert_eq!(bind(&x, "list --size=1").ok().unwrap().get(0), Some("--size=1"));
+    }
+
+    #[test]
+    fn shape_misses_are_not_errors() {
+        let x: CommandExecution<()> = x_decl! {"report", "",
+            w "^report$", v "^md$|^csv$" as Choice, v "^.*$", |_this, _args, _db| { Ok(()) }};
+        assert!(bind(&x, "report md out.md").is_ok());
+        assert!(matches!(bind(&x, "export md out.md"), Err(Miss::Shape)));
+        assert!(matches!(bind(&x, "report md"), Err(Miss::Shape)));
+        assert!(matches!(bind(&x, "report md out.md more"), Err(Miss::Shape)));
+        assert_eq!(arg_err(bind(&x, "report pdf out.pdf")), "argument 2 `pdf` does not match ^md$|^csv$");
+    }
+
+    #[test]
+    fn the_first_pattern_that_fits_runs() {
+        let xs: Vec<CommandExecution<Vec<&'static str>>> = x_decl! {
+            ("pj new", "", w "^pj$", w "^new$", v "^[a-z]+$", |this, _args, _db| { this.push("new"); Ok(()) })
+            ("pj", "", w "^pj$", v "^.+$", |this, _args, _db| { this.push("pj"); Ok(()) })
+        };
+        let mut db = crate::DataBase::new(0);
+        let mut ran = vec![];
+        let mut run = |line: &str| xs.try_execute(&line.split(' ').collect(), &mut ran, &mut db);
+        assert_eq!(run("pj new abc"), Ok(true));
+        assert_eq!(run("pj abc"), Ok(true));
+        assert_eq!(run("ev abc"), Ok(false));
+        // a malformed argument is only an error when no other pattern fits
+        assert_eq!(run("pj new ABC"), Err(String::from("argument 3 `ABC` does not match ^[a-z]+$")));
+        assert_eq!(ran, ["new", "pj"]);
+    }
+}
@@ -0,0 +1,89 @@
+//! global command history, shared by all pages and kept in history.yaml
+use serde::*;
+
+// oldest commands are dropped beyond this
+const MAX_ENTRIES: usize = 4096;
+// recent runs of a command kept for frecency
+const MAX_TIMES: usize = 10;
+
+// a distinct command line and how it went the last time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistEntry {
+    pub command: String,
+    // utc seconds of the latest runs, oldest first
+    pub times: Vec<i64>,
+    pub runs: usize,
+    // page the command was last run on
+    pub page: usize,
+    // error of the last run
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl HistEntry {
+    pub fn time(&self) -> i64 {
+        self.times.last().copied().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    // most recent last, no command appears twice
+    entries: Vec<HistEntry>,
+}
+
+impl History {
+    pub fn load_yaml(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_reader::<_, Self>(std::fs::File::open(path)?)?)
+    }
+    pub fn save_yaml(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(serde_json::to_writer(std::fs::File::create(path)?, self)?)
+    }
+    // record a run, a command run before moves to the end
+    pub fn push(&mut self, command: impl Into<String>, page: usize, error: Option<String>) {
+        let command = command.into();
+        let time = chrono::Utc::now().timestamp();
+        let mut entry = match self.entries.iter().position(|e| e.command == command) {
+            Some(i) => self.entries.remove(i),
+            None => HistEntry { command, times: vec![], runs: 0, page, error: None },
+        };
+        entry.times.push(time);
+        if entry.times.len() > MAX_TIMES { entry.times.remove(0); }
+        entry.runs += 1;
+        entry.page = page;
+        entry.error = error;
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+    }
+    // forget commands last run on a page
+    pub fn clear_page(&mut self, page: usize) {
+        self.entries.retain(|e| e.page != page);
+    }
+    // most recent first
+    pub fn iter(&self) -> impl Iterator<Item = &HistEntry> {
+        self.entries.iter().rev()
+    }
+    // the n-th most recent command containing the query, ignoring case
+    pub fn search(&self, query: &str, n: usize) -> Option<&HistEntry> {
+        let query = query.to_lowercase();
+        self.iter().filter(|e| e.command.to_lowercase().contains(&query)).nth(n)
+    }
+    // commands that succeeded the last time with their frecency, highest first
+    // recent runs count by their age like the buckets of firefox's url bar, scaled to all runs
+    pub fn frecency(&self, now: i64) -> Vec<(&str, f64)> {
+        let mut score = self.entries.iter().filter(|e| e.error.is_none()).map(|e| {
+            let sum = e.times.iter().map(|t| match (now - t) / (24 * 60 * 60) {
+                ..=4 => 100.0,
+                5..=14 => 70.0,
+                15..=31 => 50.0,
+                32..=90 => 30.0,
+                _ => 10.0,
+            }).sum::<f64>();
+            (e.command.as_str(), sum * e.runs as f64 / e.times.len().max(1) as f64)
+        }).collect::<Vec<_>>();
+        score.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+        score
+    }
+}
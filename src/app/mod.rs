@@ -1,12 +1,17 @@
 use serde::*;
 use tui::layout::Rect;
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 mod grid;
 mod viewer;
 mod plugin;
 mod execute;
 mod command;
+mod alias;
+mod prompt;
+mod history;
+mod complete;
+mod value;
 
 use grid::*;
 use viewer::*;
@@ -27,8 +32,9 @@ pub struct App {
     exeinfo: String,
     // previous generated prompts, stored as newline-seperated strings
     prompts: String,
-    // history
-    history: Vec<String>,
+    // commands run on all pages, loaded separately from history.yaml
+    #[serde(skip)]
+    history: history::History,
     // selected prompt, correspondent xcursor is encapsuled in command
     ycursor: Option<usize>,
     // view port generators, recomputed only on command triggering
@@ -42,18 +48,42 @@ pub struct App {
     #[serde(default)]
     #[serde(skip_serializing)]
     sigexit: bool,
+    // user configuration, loaded separately on every start
+    #[serde(skip)]
+    config: crate::config::Config,
+    // why config.yaml could not be loaded, it is not saved over while this is set
+    #[serde(skip)]
+    config_err: Option<String>,
+    // depth of aliases and scripts being run
+    #[serde(skip)]
+    nesting: usize,
+    // commands run by the line being executed, aliases expanded, with how each went
+    #[serde(skip)]
+    ran: Vec<(String, Option<String>)>,
+    // prompts and history matching the command line
+    #[serde(skip)]
+    completion: complete::Completion,
+    // reverse incremental search: query and how many older matches are skipped
+    #[serde(skip)]
+    search: Option<(String, usize)>,
+    // the viewer of the current page that takes keys, none for the command line
+    #[serde(skip)]
+    focus: Option<usize>,
 }
 
+// maximal nesting of aliases and scripts that call each other
+const MAX_NESTING: usize = 16;
+
 lazy_static::lazy_static!{
     // internal command implementations
     static ref EXES: Vec<execute::CommandExecution<App>> = execute::x_decl! {
-        // exit application
-        (w "^exit$", |this, _args, _db| {
+        ("exit", "exit tman, the state is saved",
+            w "^exit$", |this, _args, _db| {
             // modify exit state to true
             this.sigexit = true; Ok(())
         })
-        // set grid for viewer layout
-        (w "^grid$", v r"^[1-4][1-4]$", |this, args, _db| {
+        ("grid", "set the grid of the viewer layout, viewers are hidden until placed again", ["grid 22"],
+            w "^grid$", v r"^[1-4][1-4]$" as Grid : "grid" "rows and columns, each from 1 to 4", |this, args, _db| {
             // cols <= 4 and rows <= 4
             let grid = args[0].parse::<u16>().unwrap();
             this.layouts[this.current] = (grid / 10, grid % 10);
@@ -61,14 +91,14 @@ lazy_static::lazy_static!{
             for (_viewer, grid) in viewers { *grid = None }
             Ok(())
         })
-        // clear current page
-        (w "^clear$", |this, _args, _db| {
+        ("clear", "remove all viewers and plugins of the current page",
+            w "^clear$", |this, _args, _db| {
             this.viewers[this.current].clear();
             this.plugins[this.current].clear();
             Ok(())
         })
-        // create a page
-        (w "^page|pg$", w r"\+|new|create", |this, _args, db| {
+        ("page new", "create a page after the current one, at most 10 pages",
+            w "^page|pg$", w r"\+|new|create", |this, _args, db| {
             // insert a page after current page
             // this is potentially slow
             if this.viewers.len() >= 10 {
@@ -78,12 +108,11 @@ lazy_static::lazy_static!{
             this.viewers.insert(this.current, vec![]);
             this.layouts.insert(this.current, (1, 1));
             this.plugins.insert(this.current, vec![]);
-            this.history.insert(this.current, String::new());
             this.refresh(db);
             Ok(())
         })
-        // delete current page
-        (w "^page|pg$", w r"-|del|delete", |this, _args, db| {
+        ("page delete", "delete the current page",
+            w "^page|pg$", w r"-|del|delete", |this, _args, db| {
             // remove a current page
             if this.viewers.len() == 1 {
                 Err(String::from("cannot delete a page when there is only one page"))?
@@ -91,26 +120,112 @@ lazy_static::lazy_static!{
             this.viewers.remove(this.current);
             this.layouts.remove(this.current);
             this.plugins.remove(this.current);
-            this.history.insert(this.current, String::new());
             this.current = if this.current == 0 { 0 } else { this.current - 1 };
             this.refresh(db);
             Ok(())
         })
-        // switch page
-        (w "^page|pg$", v r"[0-9]*", |this, args, db| {
+        ("page", "switch to a page by its number",
+            w "^page|pg$", v r"[0-9]*" as Page : "page" "number of the page, counted from 0", |this, args, db| {
             // switch page by a page number
             this.current = args[0].parse::<usize>()
                 .unwrap().min(this.plugins.len()-1);
             this.refresh(db);
             Ok(())
         })
-        // clean history of current page
-        (w "^hist|history$", w r"clean|clear", |this, _args, _db| {
-            this.history[this.current].clear();
+        ("history clear", "clean the command history of the current page",
+            w "^hist$|^history$", w "^clean$|^clear$", |this, _args, _db| {
+            this.history.clear_page(this.current);
+            Ok(())
+        })
+        ("history close", "remove the history viewer",
+            w "^hist$|^history$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<HistoryView>("history viewer")
+        })
+        ("history", "show past commands with their outcomes, only those containing a query if it is given", ["history", "history 00:01 report"],
+            w "^hist$|^history$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o "^.*$" : "query" "part of the command", |this, args, _db| {
+            let query = args.get(1).unwrap_or_default();
+            // one history viewer a page, a new query replaces the old one
+            this.open_viewer(&args, |history: &mut HistoryView| { history.set_query(query); Ok(()) }, || Ok(HistoryView::new(query)))
+        })
+        ("sync caldav", "synchronize events with the caldav collection in config.yaml",
+            w "^sync$", w "^caldav$|^dav$", |this, _args, db| {
+            use crate::interop::caldav::*;
+            let config = this.config.caldav.clone().ok_or(SyncErr::NotConfigured.to_string())?;
+            let path = format!("{}/caldav.yaml", crate::PATH);
+            let mut state = SyncState::load_yaml(&path).unwrap_or_default();
+            let report = CalDavClient::new(config).sync(db, &mut state);
+            // keep the state of what was done even if the sync stopped halfway
+            state.save_yaml(&path).map_err(|e| e.to_string())?;
+            this.exeinfo = report.map_err(|e| e.to_string())?.summary();
+            Ok(())
+        })
+        ("import taskwarrior", "import a taskwarrior json export", ["import tw tasks.json"],
+            w "^import$", w "^tw$|^taskwarrior$", v "^.*$" : "file" "output of task export", |this, args, db| {
+            let json = std::fs::read_to_string(args[0]).map_err(|e| e.to_string())?;
+            this.exeinfo = crate::interop::taskwarrior::import(db, &json)?.summary();
+            Ok(())
+        })
+        ("import todotxt", "import a todo.txt file", ["import todo todo.txt"],
+            w "^import$", w "^todo$|^todotxt$", v "^.*$" : "file" "a todo.txt file", |this, args, db| {
+            let text = std::fs::read_to_string(args[0]).map_err(|e| e.to_string())?;
+            this.exeinfo = crate::interop::todotxt::import(db, &text).summary();
+            Ok(())
+        })
+        ("org export", "write the project tree to an org file", ["org export tman.org"],
+            w "^org$", w "^export$", v "^.*$" : "file" "org file to write", |this, args, db| {
+            std::fs::write(args[0], crate::interop::org::export(db)).map_err(|e| e.to_string())?;
+            this.exeinfo = format!("exported to {}", args[0]);
             Ok(())
         })
-        // color block for testing
-        (w "^color$", w "^block$", v r"^[0-3]{2}:[0-3]{2}", v r"^[a-f0-9]{6}$", |this, args, _db| {
+        ("org import", "apply an org file to the database, undone as a whole by undo", ["org import tman.org"],
+            w "^org$", w "^import$", v "^.*$" : "file" "org file written by org export", |this, args, db| {
+            let text = std::fs::read_to_string(args[0]).map_err(|e| e.to_string())?;
+            this.exeinfo = crate::interop::org::import(db, &text).summary();
+            Ok(())
+        })
+        ("report", "write a progress report, of a subtree down to a depth and with events between two dates if given",
+            ["report md report.md", "report csv work.csv --root=work --depth=2 --from=2026/10/01 --to=2026/10/31"],
+            w "^report$", v "^md$|^csv$" as Choice : "format" "md or csv", v "^.*$" : "file" "where the report is written",
+            k "root" "^.+$" as Project = "root" : "project" "root of the subtree",
+            k "depth" r"^\d+$" : "n" "levels shown below the root",
+            k "from" r"^\d{4}/\d{2}/\d{2}$" as Date : "date" "first day of the events",
+            k "to" r"^\d{4}/\d{2}/\d{2}$" as Date : "date" "last day of the events",
+        |this, args, db| {
+            use crate::interop::report::*;
+            let mut opt = ReportOpt::new(args.parse(0)?);
+            opt.root = args[2].to_string();
+            if args.get(3).is_some() { opt.depth = Some(args.parse(3)?) }
+            if args.get(4).is_some() { opt.from = args.parse_with(4, |s| ReportOpt::parse_date(s, db))? }
+            // the end date is included
+            if args.get(5).is_some() { opt.to = args.parse_with(5, |s| ReportOpt::parse_date(s, db))? + 24 * 60 * 60 }
+            std::fs::write(args[1], render(db, &opt)?).map_err(|e| e.to_string())?;
+            this.exeinfo = format!("report written to {}", args[1]);
+            Ok(())
+        })
+        ("source", "run a script of tman commands line by line, undone as a whole by undo", ["source setup.tman"],
+            w "^source$", v "^.*$" : "file" "script, # starts a comment line", |this, args, db| {
+            this.source(args[0], db)
+        })
+        ("help close", "remove the help viewer",
+            w "^help$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<HelpView>("help viewer")
+        })
+        ("help", "show the commands of this page, only those matching a query if it is given", ["help", "help report", "help 00:01 page"],
+            w "^help$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o "^.*$" : "query" "part of a command name, summary or syntax", |this, args, _db| {
+            let query = args.get(1).unwrap_or_default();
+            // one help viewer a page, a new query replaces the old one
+            this.open_viewer(&args, |help: &mut HelpView| { help.set_query(query); Ok(()) }, || Ok(HelpView::new(query)))
+        })
+        ("undo", "revert the last change to the database",
+            w "^undo$", |_this, _args, db| {
+            if !db.undo() { Err(String::from("nothing to undo"))? }
+            Ok(())
+        })
+        ("color block", "show a block of color, for testing", ["color block 00:00 ff8800"],
+            w "^color$", w "^block$", v r"^[0-3]{2}:[0-3]{2}" as Corner : "corner" "top left and bottom right cells, like 00:11",
+            v r"^[a-f0-9]{6}$" as Color : "color" "hex rgb without #", |this, args, _db| {
             let cb = usize::from_str_radix(args[1].trim_start_matches("0"), 16).unwrap_or(0);
             let cb = ColorBlock::new((cb / (256 * 256)) as u8, (cb / 256 % 256) as u8, (cb % 256) as u8);
             let (rows, cols) = this.layouts[this.current];
@@ -118,51 +233,346 @@ lazy_static::lazy_static!{
                 (cb.into(), GridLayout::corner_from(args[0], rows, cols)));
             Ok(())
         })
-        // display project list
-        (w "^list$", v r"^[0-3]{2}:[0-3]{2}$", v "^.*$", |this, args, db| {
-            // add a list viewer
-            todo!("implement project list viewer")
-        })
-        // display auto planner with year-month-date
-        (w "^plan|planner$", v r"[0-3]{2}:[0-3]{2}", v r"\d{4}/\d{2}/\d{2}", |this, args, db| {
-            // put planner on a given viewport for a given date
-            todo!("implement auto planner, add planner commands")
-        })
-        // display auto planner this week
-        (w "^plan|planner$", v r"[0-3]{2}:[0-3]{2}", v r"\d{4}/\d{2}/\d{2}", |this, args, db| {
-            // put planner on a given viewport for a given date
-            todo!("implement auto planner, add planner commands")
-        })
-        // display month calendar
-        (w "^cal|calender$", v r"[0-3]{2}:[0-3]{2}", v r"\d{4}/\d{2}", |this, args, db| {
-            todo!("remove current calendar and put a new one")
-        })
-        // display year calendar
-        (w "^cal|calender$", v r"[0-3]{2}:[0-3]{2}", v r"\d{4}", |this, args, db| {
-            todo!("remove current calendar and put a new one")
-        })
-        // project editor plugin and project viewer
-        (w "^ed|edit|editor$", v r"[0-3]{2}:[0-3]{2}", w "^pj|proj|project$", v "^.*$", |this, args, db| {
-            let name = args[1];
-            let pj = db.pj_get_or_create_by_name(&name);
-            // TODO: remove current editor plugins or viewers if there is any
-            // TODO: add an editor plugin
-            // add editor viewer
-            let ed = EditorView::new_pj(pj, db);
-            let (rows, cols) = this.layouts[this.current];
-            this.viewers[this.current].push(
-                (ed.into(), GridLayout::corner_from(&args[0], rows, cols))
-            );
+        ("pj new", "create a project, under a parent if given", ["pj new \"house move\"", "pj new packing \"house move\""],
+            w "^pj$|^proj$|^project$", w r"^new$|^\+$", v "^.+$" : "name" "name of the new project",
+            o "^.+$" as Project = "root" : "parent" "project it belongs to", |_this, args, db| {
+            let pp = value::pj_id(db, args[1]).map_err(|e| args.err(1, e))?;
+            db.pj_create(args[0], pp).map_err(|e| args.err(0, e))?;
+            Ok(())
+        })
+        ("pj rm", "delete a project without children or dependencies",
+            w "^pj$|^proj$|^project$", w r"^rm$|^del$|^delete$|^-$", v "^.+$" as Project : "project" "project to delete", |_this, args, db| {
+            let id = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            db.pj_delete(id).map_err(|e| args.err(0, e))?;
+            Ok(())
+        })
+        ("pj mv", "move a project under another parent, its dependencies must be removed first", ["pj mv packing root"],
+            w "^pj$|^proj$|^project$", w "^mv$|^move$", v "^.+$" as Project : "project" "project to move",
+            v "^.+$" as Project : "parent" "new parent", |_this, args, db| {
+            let id = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            let pp = value::pj_id(db, args[1]).map_err(|e| args.err(1, e))?;
+            db.pj_set_pp(id, pp).map_err(|e| args.err(1, e))?;
+            Ok(())
+        })
+        ("pj dep+", "make a project start after others of the same parent", ["pj dep+ unpacking packing moving"],
+            w "^pj$|^proj$|^project$", w r"^dep\+$", v "^.+$" as Project : "project" "project that waits",
+            m "^.+$" as Project : "dependency" "projects it waits for", |_this, args, db| {
+            let id = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            for dep in args.all(1) {
+                value::pj_id(db, dep).and_then(|d| db.pj_add_deps(id, d).map_err(|e| e.to_string()))
+                    .map_err(|e| format!("{dep}: {e}"))?;
+            }
+            Ok(())
+        })
+        ("pj dep-", "remove dependencies of a project", ["pj dep- unpacking packing"],
+            w "^pj$|^proj$|^project$", w "^dep-$", v "^.+$" as Project : "project" "project that waits",
+            m "^.+$" as Project : "dependency" "projects it no longer waits for", |_this, args, db| {
+            let id = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            for dep in args.all(1) {
+                value::pj_id(db, dep).and_then(|d| db.pj_rmv_deps(id, d).map_err(|e| e.to_string()))
+                    .map_err(|e| format!("{dep}: {e}"))?;
+            }
+            Ok(())
+        })
+        ("pj due", "set the deadline of a project, - removes it", ["pj due packing 2026/11/01 17:00", "pj due packing -"],
+            w "^pj$|^proj$|^project$", w "^due$|^deadline$", v "^.+$" as Project : "project" "project with the deadline",
+            v r"^\d{4}/\d{2}/\d{2}$|^-$" as Date : "date" "YYYY/MM/DD or -",
+            o r"^\d{1,2}:\d{2}$" : "time" "HH:MM, the end of the day by default", |_this, args, db| {
+            let id = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            let due = match args[1] {
+                "-" => None,
+                date => Some(value::time(date, args.get(2), db).map_err(|e| args.err(1, e))?),
+            };
+            db.pj_set_deadline(id, due).map_err(|e| args.err(0, e))?;
+            Ok(())
+        })
+        ("pj weight", "set the weight of a project in planning, and whether its time is flexible or reserved", ["pj weight packing 3 flexible"],
+            w "^pj$|^proj$|^project$", w "^weight$", v "^.+$" as Project : "project" "project to weigh",
+            v r"^\d+$" : "weight" "relative share of planned time",
+            o "^flex$|^flexible$|^rsv$|^reserved$" as Choice : "type" "flexible or reserved, unchanged by default", |_this, args, db| {
+            let id = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            db.pj_set_weight(id, args.parse(1)?).map_err(|e| args.err(1, e))?;
+            if args.get(2).is_some() {
+                db.pj_set_weight_type(id, args.parse_with(2, value::weight_type)?).map_err(|e| args.err(2, e))?;
+            }
+            Ok(())
+        })
+        ("pj quota", "set the estimated quota of a project in hours, and the quota done if given", ["pj quota packing 12", "pj quota packing 12 5"],
+            w "^pj$|^proj$|^project$", w "^quota$", v "^.+$" as Project : "project" "project to estimate",
+            v r"^\d+$" : "estimated" "hours the project takes", o r"^\d+$" : "done" "hours already spent", |_this, args, db| {
+            let id = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            db.pj_set_quota_esti(id, args.parse(1)?).map_err(|e| args.err(1, e))?;
+            if args.get(2).is_some() {
+                db.pj_set_quota_done(id, args.parse(2)?).map_err(|e| args.err(2, e))?;
+            }
+            Ok(())
+        })
+        ("pj color", "set the color of a project", ["pj color packing ff8800"],
+            w "^pj$|^proj$|^project$", w "^color$|^colour$", v "^.+$" as Project : "project" "project to paint",
+            v r"^#?[0-9a-fA-F]{6}$" as Color : "color" "hex rgb like ff8800", |_this, args, db| {
+            let id = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            db.pj_set_color(id, args.parse_with(1, value::color)?).map_err(|e| args.err(1, e))?;
+            Ok(())
+        })
+        ("ev new", "create an event of a project at a time, taking an estimated quota", ["ev new \"pack books\" packing 2026/10/20 14:00 2"],
+            w "^ev$|^event$", w r"^new$|^\+$", v "^.+$" : "name" "name of the new event",
+            v "^.+$" as Project : "project" "project it belongs to",
+            v r"^\d{4}/\d{2}/\d{2}$" as Date : "date" "YYYY/MM/DD", v r"^\d{1,2}:\d{2}$" : "time" "HH:MM",
+            o r"^\d+$" = "1" : "quota" "estimated hours", |_this, args, db| {
+            let pp = value::pj_id(db, args[1]).map_err(|e| args.err(1, e))?;
+            let time = value::time(args[2], Some(args[3]), db).map_err(|e| args.err(2, e))?;
+            let ev = crate::data::Ev::new(args[0].to_string(), pp, time, args.parse(4)?);
+            db.ev_create(ev).map_err(|e| args.err(0, e))?;
             Ok(())
         })
-        // event editor plugin and event viewer
-        (w "^ed|edit|editor$", v r"[0-3]{2}:[0-3]{2}", w "^ev|event$", v "^.*$", |this, args, db| {
-            // remove current editor plugins or viewers if there is any
-            todo!("implement event editor plugin")
+        ("ev rm", "delete an event",
+            w "^ev$|^event$", w r"^rm$|^del$|^delete$|^-$", v "^.+$" as Event : "event" "event to delete", |_this, args, db| {
+            let id = value::ev_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            db.ev_delete(id).map_err(|e| args.err(0, e))?;
+            Ok(())
+        })
+        ("ev mv", "move an event to another project", ["ev mv \"pack books\" moving"],
+            w "^ev$|^event$", w "^mv$|^move$", v "^.+$" as Event : "event" "event to move",
+            v "^.+$" as Project : "project" "new parent", |_this, args, db| {
+            let id = value::ev_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            let pp = value::pj_id(db, args[1]).map_err(|e| args.err(1, e))?;
+            db.ev_set_pp(id, pp).map_err(|e| args.err(1, e))?;
+            Ok(())
+        })
+        ("ev name", "rename an event", ["ev name \"pack books\" \"pack the books\""],
+            w "^ev$|^event$", w "^name$|^rename$", v "^.+$" as Event : "event" "event to rename",
+            v "^.+$" : "name" "new name", |_this, args, db| {
+            let id = value::ev_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            db.ev_set_name(id, args[1].to_string()).map_err(|e| args.err(1, e))?;
+            Ok(())
+        })
+        ("ev time", "move an event to another time", ["ev time \"pack books\" 2026/10/21 09:00"],
+            w "^ev$|^event$", w "^time$|^at$", v "^.+$" as Event : "event" "event to move",
+            v r"^\d{4}/\d{2}/\d{2}$" as Date : "date" "YYYY/MM/DD", v r"^\d{1,2}:\d{2}$" : "time" "HH:MM", |_this, args, db| {
+            let id = value::ev_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            let time = value::time(args[1], Some(args[2]), db).map_err(|e| args.err(1, e))?;
+            db.ev_set_time(id, time).map_err(|e| args.err(1, e))?;
+            Ok(())
+        })
+        ("ev quota", "set the estimated quota of an event in hours", ["ev quota \"pack books\" 3"],
+            w "^ev$|^event$", w "^quota$", v "^.+$" as Event : "event" "event to estimate",
+            v r"^\d+$" : "estimated" "hours the event takes", |_this, args, db| {
+            let id = value::ev_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            db.ev_set_quota_esti(id, args.parse(1)?).map_err(|e| args.err(1, e))?;
+            Ok(())
+        })
+        ("list close", "remove the list viewer",
+            w "^list$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<ListView>("list viewer")
+        })
+        ("list", "list the projects and events meeting every term of a filter, sorted by a key",
+            ["list", "list 00:01 overdue --sort=due", "list \"parent:work flexible\" --sort=progress", "list ^pack ev"],
+            w "^list$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o "^.*$" : "filter" "name regex, parent:<project>, due, overdue, flexible, reserved, pj or ev",
+            k "sort" "^name$|^due$|^progress$|^weight$" as Choice = "name" : "key" "name, due, progress or weight", |this, args, _db| {
+            let filter = args.get(1).unwrap_or_default();
+            let sort = args.parse(2)?;
+            // one list viewer a page, a new filter replaces the old one
+            this.open_viewer(&args, |list: &mut ListView| list.set(filter, sort).map_err(|e| args.err(1, e)),
+                || ListView::new(filter, sort).map_err(|e| args.err(1, e)))
+        })
+        ("graph close", "remove the dependency graph",
+            w "^graph$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<GraphView>("graph")
+        })
+        ("graph", "show the dependencies among the children of a project, with the critical path and blocked projects",
+            ["graph home", "graph 01:11 \"house move\""],
+            w "^graph$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o "^.+$" as Project = "root" : "project" "parent of the projects shown", |this, args, db| {
+            let parent = value::pj_id(db, args[1]).map_err(|e| args.err(1, e))?;
+            // one graph a page, a new project replaces the old one
+            this.open_viewer(&args, |graph: &mut GraphView| { graph.set_parent(parent); Ok(()) }, || Ok(GraphView::new(parent)))
+        })
+        ("gantt close", "remove the gantt chart",
+            w "^gantt$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<GanttView>("gantt chart")
+        })
+        ("gantt", "chart the work left on every project below a project, from its planned start to its deadline",
+            ["gantt home", "gantt 10:11 work --zoom=month"],
+            w "^gantt$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o "^.+$" as Project = "root" : "project" "top of the projects shown",
+            k "zoom" "^day$|^week$|^month$" as Choice = "week" : "zoom" "a column for a quarter of a day, a day or a week", |this, args, db| {
+            let root = value::pj_id(db, args[1]).map_err(|e| args.err(1, e))?;
+            let zoom = args.parse(2)?;
+            // one gantt chart a page, a new project replaces the old one
+            this.open_viewer(&args, |gantt: &mut GanttView| { gantt.set(root, zoom); Ok(()) }, || Ok(GanttView::new(root, zoom)))
+        })
+        ("burn close", "remove the burndown chart",
+            w "^burn$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<BurnView>("burndown chart")
+        })
+        ("burn", "chart the hours left on a project and the projects below it against the ideal pace to its deadline",
+            ["burn sprint", "burn 01:11 sprint --chart=up"],
+            w "^burn$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o "^.+$" as Project = "root" : "project" "top of the projects charted",
+            k "chart" "^down$|^up$" as Choice = "down" : "chart" "down for hours left, up for hours done and estimated", |this, args, db| {
+            let root = value::pj_id(db, args[1]).map_err(|e| args.err(1, e))?;
+            let burn = args.parse(2)?;
+            // one burndown chart a page, a new project replaces the old one
+            this.open_viewer(&args, |chart: &mut BurnView| { chart.set(root, burn); Ok(()) }, || Ok(BurnView::new(root, burn)))
+        })
+        ("heat close", "remove the activity heatmap",
+            w "^heat$|^heatmap$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<HeatView>("heatmap")
+        })
+        ("heat", "show the hours worked each day of the last year on a project and the projects below it",
+            ["heat home", "heat 10:11 work --count=done"],
+            w "^heat$|^heatmap$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o "^.+$" as Project = "root" : "project" "top of the projects counted",
+            k "count" "^events$|^done$" as Choice = "events" : "count" "hours of past events, or quota marked done", |this, args, db| {
+            let root = value::pj_id(db, args[1]).map_err(|e| args.err(1, e))?;
+            let count = args.parse(2)?;
+            // one heatmap a page, a new project replaces the old one
+            this.open_viewer(&args, |heat: &mut HeatView| { heat.set(root, count); Ok(()) }, || Ok(HeatView::new(root, count)))
+        })
+        ("tree close", "remove the project tree",
+            w "^tree$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<TreeView>("tree")
+        })
+        ("tree mv", "move the selected node of the tree, with everything below it, under another project", ["tree mv archive"],
+            w "^tree$", w "^mv$|^move$", v "^.+$" as Project : "parent" "new parent", |this, args, db| {
+            let pp = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            match this.tree_node()? {
+                Node::Pj(id) => db.pj_set_pp(id, pp).map_err(|e| args.err(0, e)),
+                Node::Ev(id) => db.ev_set_pp(id, pp).map_err(|e| args.err(0, e)),
+            }
+        })
+        ("tree new", "create a project under the selected node of the tree, or beside the selected event", ["tree new packing"],
+            w "^tree$", w "^new$|^\\+$", v "^.+$" : "name" "name of the project", |this, args, db| {
+            let pp = match this.tree_node()? {
+                Node::Pj(id) => id,
+                Node::Ev(id) => db.ev_get_by_id(id).map(|ev| ev.pp()).unwrap_or(0),
+            };
+            db.pj_create(args[0], pp).map_err(|e| args.err(0, e))?;
+            Ok(())
+        })
+        ("tree rm", "delete the selected node of the tree",
+            w "^tree$", w "^rm$|^del$|^delete$|^-$", |this, _args, db| {
+            match this.tree_node()? {
+                Node::Pj(id) => db.pj_delete(id).map_err(|e| e.to_string()),
+                Node::Ev(id) => db.ev_delete(id).map_err(|e| e.to_string()),
+            }
+        })
+        ("tree", "show projects as a tree with their events, below the root if no project is given", ["tree", "tree 00:10 \"house move\""],
+            w "^tree$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o "^.+$" as Project = "root" : "project" "top of the tree", |this, args, db| {
+            let root = value::pj_id(db, args[1]).map_err(|e| args.err(1, e))?;
+            // one tree a page, a new root replaces the old one
+            this.open_viewer(&args, |tree: &mut TreeView| { tree.set_root(root); Ok(()) }, || Ok(TreeView::new(root)))
+        })
+        ("plan close", "remove the planner",
+            w "^plan$|^planner$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<PlanView>("planner")
+        })
+        ("plan next", "move the planner to the next week",
+            w "^plan$|^planner$", w "^next$|^>$", |this, _args, _db| {
+            this.planner()?.shift(1);
+            Ok(())
+        })
+        ("plan prev", "move the planner to the previous week",
+            w "^plan$|^planner$", w "^prev$|^previous$|^<$", |this, _args, _db| {
+            this.planner()?.shift(-1);
+            Ok(())
+        })
+        ("plan accept", "turn proposed blocks of the planner into events", ["plan accept 1 2", "plan accept all"],
+            w "^plan$|^planner$", w "^accept$|^ok$|^\\+$", m r"^\d+$|^all$" : "proposal" "number of a proposed block, or all", |this, args, db| {
+            let proposals = this.proposals(args.all(0))?;
+            for (n, p) in proposals {
+                let name = PlanView::event_name(db, &p);
+                db.ev_create(crate::data::Ev::new(name, p.pj, p.time, p.quota)).map_err(|e| format!("#{n}: {e}"))?;
+            }
+            Ok(())
+        })
+        ("plan reject", "drop proposed blocks of the planner, their hours are left free", ["plan reject 3", "plan reject all"],
+            w "^plan$|^planner$", w "^reject$|^no$|^-$", m r"^\d+$|^all$" : "proposal" "number of a proposed block, or all", |this, args, _db| {
+            let proposals = this.proposals(args.all(0))?;
+            let plan = this.planner()?;
+            for (_, p) in proposals { plan.reject(&p) }
+            Ok(())
+        })
+        ("plan move", "turn a proposed block of the planner into an event at another time", ["plan move 2 2026/10/22 14:00"],
+            w "^plan$|^planner$", w "^move$|^mv$", v r"^\d+$" : "proposal" "number of a proposed block",
+            v r"^\d{4}/\d{2}/\d{2}$" as Date : "date" "YYYY/MM/DD", v r"^\d{1,2}:\d{2}$" : "time" "HH:MM", |this, args, db| {
+            let (_, p) = this.proposals(&args.all(0)[..1])?.remove(0);
+            let time = value::time(args[1], Some(args[2]), db).map_err(|e| args.err(1, e))?;
+            let p = crate::data::Proposal { time, ..p };
+            db.ev_create(crate::data::Ev::new(PlanView::event_name(db, &p), p.pj, p.time, p.quota)).map_err(|e| args.err(1, e))?;
+            Ok(())
+        })
+        ("plan", "show a week of events and the blocks the auto planner proposes, this week if no date is given",
+            ["plan", "plan 00:11 2026/10/26"],
+            w "^plan$|^planner$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o r"^\d{4}/\d{2}/\d{2}$" as Date : "date" "a day of the week, YYYY/MM/DD", |this, args, db| {
+            let date = match args.get(1) {
+                Some(date) => value::local(date, None).map_err(|e| args.err(1, e))?.date(),
+                None => db.datetime_loc().map_err(|e| e.to_string())?.date(),
+            };
+            // one planner a page, a new date replaces the old one
+            this.open_viewer(&args, |plan: &mut PlanView| { plan.set_date(date); Ok(()) }, || Ok(PlanView::new(date)))
+        })
+        ("cal close", "remove the calendar",
+            w "^cal$|^calendar$", w "^close$|^stop$|^-$", |this, _args, _db| {
+            this.close_viewer::<CalView>("calendar")
+        })
+        ("cal next", "move the calendar to the next month, or year",
+            w "^cal$|^calendar$", w "^next$|^>$", |this, _args, _db| {
+            this.shift_calendar(1)
+        })
+        ("cal prev", "move the calendar to the previous month, or year",
+            w "^cal$|^calendar$", w "^prev$|^previous$|^<$", |this, _args, _db| {
+            this.shift_calendar(-1)
         })
-        // remove editor if there is any, else display an error message
-        (w "^ed|edit|editor", v r"-|stop|quit|exit|remove", |this, args, db| {
-            todo!("remove editor plugin and viewer")
+        ("cal year", "display the calendar of a year, days are shaded by hours of events", ["cal 2026", "cal 00:11 2027"],
+            w "^cal$|^calendar$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            v r"^\d{4}$" : "year" "YYYY", |this, args, _db| {
+            let span = CalSpan::Year(args.parse(1)?);
+            this.open_viewer(&args, |cal: &mut CalView| { cal.set_span(span); Ok(()) }, || Ok(CalView::new(span)))
+        })
+        ("cal month", "display the calendar of a month, this month if none is given", ["cal", "cal 00:11 2026/11"],
+            w "^cal$|^calendar$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, the whole page by default",
+            o r"^\d{4}/\d{2}$" : "month" "YYYY/MM", |this, args, db| {
+            let span = match args.get(1) {
+                Some(_) => args.parse_with(1, |s| {
+                    use chrono::Datelike;
+                    chrono::NaiveDate::parse_from_str(&format!("{s}/01"), "%Y/%m/%d")
+                        .map(|d| CalSpan::Month(d.year(), d.month())).map_err(|_| "is not a month")
+                })?,
+                None => {
+                    use chrono::Datelike;
+                    let today = db.datetime_loc().map_err(|e| e.to_string())?.date();
+                    CalSpan::Month(today.year(), today.month())
+                }
+            };
+            this.open_viewer(&args, |cal: &mut CalView| { cal.set_span(span); Ok(()) }, || Ok(CalView::new(span)))
+        })
+        ("edit project", "edit a project, it is created if it does not exist", ["ed 00:11 pj \"house move\"", "ed pj packing"],
+            w "^ed|edit|editor$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, where the editor is by default",
+            w "^pj$|^proj$|^project$", v "^.*$" as Project : "project" "name of the project", |this, args, db| {
+            let pj = db.pj_get_or_create_by_name(args[1]);
+            let plug = EditorPlug::pj(pj.id());
+            this.open_editor(EditorView::new_pj(pj, db), plug, args.get(0)).map_err(|e| args.err(0, e))
+        })
+        ("edit event", "edit an event", ["ed 00:11 ev \"pack books\""],
+            w "^ed|edit|editor$", o r"^[0-3]{2}:[0-3]{2}$" as Corner : "corner" "top left and bottom right cells, where the editor is by default",
+            w "^ev$|^event$", v "^.*$" as Event : "event" "name of the event", |this, args, db| {
+            let ev = db.ev_get_by_name(args[1]).ok_or_else(|| args.err(1, "no event has this name"))?;
+            let plug = EditorPlug::ev(ev.id());
+            this.open_editor(EditorView::new_ev(ev, db), plug, args.get(0)).map_err(|e| args.err(0, e))
+        })
+        ("edit stop", "remove the editor and its commands, it is an error if there is none",
+            w "^ed|edit|editor$", w "^stop$|^quit$|^exit$|^remove$|^-$", |this, _args, _db| {
+            let len = this.viewers[this.current].len() + this.plugins[this.current].len();
+            this.viewers[this.current].retain(|(v, _)| !matches!(v, ViewerOpt::EditorView(_)));
+            this.plugins[this.current].retain(|p| !matches!(p, PluginOpt::EditorPlug(_)));
+            if len == this.viewers[this.current].len() + this.plugins[this.current].len() {
+                Err(String::from("there is no editor on this page"))?
+            }
+            Ok(())
         })
     };
 }
@@ -175,7 +585,23 @@ impl App {
         let plugins = vec![vec![]];
         let viewers = vec![vec![]];
         let layouts = vec![(1, 1)];
-        Self { command, plugins, prompts, layouts, viewers, current: 0, ycursor: None, exeinfo, history: vec![String::new()], sigexit: false }
+        Self { command, plugins, prompts, layouts, viewers, current: 0, ycursor: None, exeinfo, history: Default::default(), sigexit: false, config: Default::default(), config_err: None, nesting: 0, ran: vec![], completion: Default::default(), search: None, focus: None }
+    }
+    // defaults if the file does not exist, a file that does not parse is reported and kept as it is
+    pub fn load_config(&mut self, path: &str) {
+        match crate::config::Config::load_yaml(path) {
+            Ok(config) => self.config = config,
+            Err(_) if !std::path::Path::new(path).exists() => self.config = Default::default(),
+            Err(e) => {
+                self.config = Default::default();
+                self.config_err = Some(format!("{path}: {e}"));
+                self.exeinfo = format!("cannot load the config, defaults are used: {path}: {e}");
+            }
+        }
+    }
+    // history starts empty if the file cannot be read
+    pub fn load_history(&mut self, path: &str) {
+        self.history = history::History::load_yaml(path).unwrap_or_default();
     }
     pub fn load_yaml(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         Ok(serde_json::from_reader::<_, Self>(std::fs::File::open(path)?)?)
@@ -190,7 +616,7 @@ impl App {
             execute,
             terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
         };
-        use std::{io, time::{Duration, Instant}};
+        use std::{io, time::Duration};
         use tui::{backend::CrosstermBackend, Terminal};
         // setup terminal
         enable_raw_mode()?;
@@ -198,21 +624,26 @@ impl App {
         execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
-        let mut last_tick = Instant::now();
-        let tick_rate = Duration::from_secs_f32(0.01);
-        // run application
+        // prompts and completion are not saved
+        self.refresh(db);
+        // run application, drawing only when something changed
+        let mut dirty = true;
         while !self.sigexit {
-            terminal.draw(|f| self.render(f))?;
-            let timeout = tick_rate
-                .checked_sub(last_tick.elapsed())
-                .unwrap_or_else(|| Duration::from_secs(0));
-            if crossterm::event::poll(timeout)? {
-                if let Event::Key(key) = event::read()? {
-                    self.key(key.code, db);
-                }
+            if dirty {
+                terminal.draw(|f| self.render(f))?;
+                dirty = false;
             }
-            if last_tick.elapsed() >= tick_rate {
-                last_tick = Instant::now();
+            // sleep until an event or the next minute, when clocks on screen change
+            let millis = chrono::Utc::now().timestamp_millis().rem_euclid(60_000) as u64;
+            if event::poll(Duration::from_millis(60_000 - millis))? {
+                match event::read()? {
+                    Event::Key(key) => { self.key(key, db); dirty = true }
+                    Event::Resize(..) => dirty = true,
+                    _ => {}
+                }
+            } else {
+                self.tick(db);
+                dirty = true;
             }
         }
         // restore terminal
@@ -242,11 +673,57 @@ impl App {
         self.render_viewers(f, Rect {
             x: rect.x, y: rect.y + _h, width: rect.width, height: rect.height - _h });
     }
-    fn key(&mut self, key: KeyCode, db: &mut crate::DataBase) {
+    fn key(&mut self, key: KeyEvent, db: &mut crate::DataBase) {
         let to_num = |x: Option<usize>| x.map(|x| x + 1).unwrap_or(0);
         let to_opt = |x: usize| (x != 0).then(|| x - 1);
-        let prompts = self.select_prompts();
-        match key {
+        let len = self.completion.len();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        if let Some(i) = self.focus {
+            match self.viewers[self.current][i].0.key(key) {
+                KeyResult::Ignored if key.code == KeyCode::Esc => self.cycle_focus(),
+                KeyResult::Ignored | KeyResult::Consumed => {}
+                KeyResult::Command(line) => {
+                    self.execute(&line, db);
+                    self.refresh(db);
+                }
+            }
+            return
+        }
+        if self.search.is_some() && !self.search_key(key) { return }
+        if ctrl && key.code == KeyCode::Char('r') {
+            self.search = Some((String::new(), 0));
+            return
+        }
+        // readline style editing
+        let edit: Option<fn(&mut Command)> = match key.code {
+            KeyCode::Home => Some(Command::home),
+            KeyCode::End => Some(Command::end),
+            KeyCode::Left if ctrl => Some(Command::word_l),
+            KeyCode::Right if ctrl => Some(Command::word_r),
+            KeyCode::Char(c) if ctrl => match c {
+                'a' => Some(Command::home),
+                'e' => Some(Command::end),
+                'w' => Some(Command::kill_word),
+                'u' => Some(Command::kill_head),
+                'k' => Some(Command::kill_tail),
+                'y' => Some(Command::yank),
+                'z' | '_' | '7' => Some(Command::undo),
+                _ => None,
+            },
+            KeyCode::Char('b') if alt => Some(Command::word_l),
+            KeyCode::Char('f') if alt => Some(Command::word_r),
+            _ => None,
+        };
+        if let Some(edit) = edit {
+            edit(&mut self.command);
+            self.ycursor = None;
+            self.completion.update(self.command.get());
+            return
+        }
+        // other control and alt chords are not typed in
+        if (ctrl || alt) && matches!(key.code, KeyCode::Char(_)) { return }
+        match key.code {
             // edit command line
             KeyCode::Char(c) => {
                 self.command.put(c);
@@ -261,88 +738,325 @@ impl App {
                 self.ycursor = None;
             },
             KeyCode::Tab => {
-                let prompts = prompts.get(self.ycursor.unwrap_or(0));
-                if prompts.is_none() { return }
-                let prompts = prompts.unwrap();
-                let capture = self.command.get_regex().find(prompts);
-                if capture.is_none() { return }
-                let capture = capture.unwrap();
-                let command = prompts[..capture.end()].to_string();
-                self.command.set(command);
+                // complete up to the end of the word of the last matched character
+                let i = self.ycursor.unwrap_or(0);
+                let Some(text) = self.completion.get(i) else { return };
+                let last = self.completion.positions(i).last().copied().unwrap_or(0);
+                let end = text[last..].find(' ').map_or(text.len(), |k| last + k);
+                self.command.set(text[..end].to_string());
                 self.ycursor = None;
             },
             // move cursor
-            KeyCode::Up => self.ycursor = to_opt((to_num(self.ycursor) + prompts.len()) % (prompts.len() + 1)),
-            KeyCode::Down => self.ycursor = to_opt((to_num(self.ycursor) + 1) % (prompts.len() + 1)),
+            KeyCode::Up => self.ycursor = to_opt((to_num(self.ycursor) + len) % (len + 1)),
+            KeyCode::Down => self.ycursor = to_opt((to_num(self.ycursor) + 1) % (len + 1)),
             KeyCode::Left => self.command.l(),
             KeyCode::Right => self.command.r(),
+            KeyCode::Esc => self.cycle_focus(),
             // trigger command
             KeyCode::Enter => {
                 // set command to prompt of some prompt is selected
-                let command = self.ycursor.map(|p| self.select_prompts()[p]);
+                let command = self.ycursor.and_then(|p| self.completion.get(p));
                 if let Some(cmd) = command {
                     self.command.set(cmd.to_string());
                 }
-                self.execute(db);
+                let line = self.command.get().trim().to_string();
+                self.execute(&line, db);
                 self.refresh(db);
                 self.ycursor = None;
             },
             _ => {}
         };
+        self.completion.update(self.command.get());
+    }
+    // give keys to the next viewer that takes them, back to the command line after the last one
+    fn cycle_focus(&mut self) {
+        let viewers = &mut self.viewers[self.current];
+        let start = self.focus.map_or(0, |i| i + 1);
+        if let Some(i) = self.focus.take() { viewers[i].0.focus(false); }
+        self.focus = (start..viewers.len()).find(|&i| viewers[i].1.is_some() && viewers[i].0.focus(true));
+    }
+    // keys of reverse incremental search, return true if the key is left to the command line
+    // enter runs the found command, other keys outside of search take it into the command line
+    fn search_key(&mut self, key: KeyEvent) -> bool {
+        let Some((query, skip)) = self.search.as_mut() else { return true };
+        let plain = !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match key.code {
+            // an older match
+            KeyCode::Char('r') if !plain => {
+                if self.history.search(query, *skip + 1).is_some() { *skip += 1 }
+                return false
+            }
+            // cancel, the command line is left as it was
+            KeyCode::Char('g') if !plain => { self.search = None; return false }
+            KeyCode::Esc => { self.search = None; return false }
+            KeyCode::Char(c) if plain => { query.push(c); *skip = 0; return false }
+            KeyCode::Backspace => { query.pop(); *skip = 0; return false }
+            _ => {}
+        }
+        if let Some(found) = self.history.search(query, *skip) {
+            self.command.set(found.command.clone());
+        }
+        self.search = None;
+        self.completion.update(self.command.get());
+        key.code == KeyCode::Enter
+    }
+    // a minute passed, viewers that show the time follow it
+    fn tick(&mut self, db: &crate::DataBase) {
+        self.refresh_viewers(db);
+    }
+    // refresh viewer cache with the user configuration and new database state
+    fn refresh_viewers(&mut self, db: &crate::DataBase) {
+        for (viewer, _) in self.viewers[self.current].iter_mut() {
+            viewer.set_config(&self.config);
+            viewer.refresh(db);
+        }
     }
     // refresh prompts command and viewer states
     fn refresh(&mut self, db: &crate::DataBase) {
         // clear prompts and command
         self.command.clear();
         // iterate over plugins and get new prompts
-        self.prompts = self.int_prompts(&db);
+        self.prompts = alias::prompts(&self.config.aliases);
+        self.prompts.push_str(&self.int_prompts(db));
         for plugin in self.plugins[self.current].iter() {
             plugin.ext_prompts(db, &mut self.prompts);
         }
-        // refresh viewer cache with new database state
+        self.refresh_viewers(db);
+        // viewers may have moved, hidden or gone since focus was given
+        let focus = self.focus.take();
+        for (i, (viewer, grid)) in self.viewers[self.current].iter_mut().enumerate() {
+            let on = focus == Some(i) && grid.is_some();
+            if viewer.focus(on) && on { self.focus = Some(i) }
+        }
+        // candidates change with prompts and history
+        self.completion.reset(&self.history, &self.prompts);
+        self.completion.update(self.command.get());
         for (viewer, _) in self.viewers[self.current].iter_mut() {
-            viewer.refresh(db);
+            if let ViewerOpt::HistoryView(history) = viewer { history.set_entries(&self.history, db.tz()) }
+        }
+        // help lists the commands of this page
+        if self.viewers[self.current].iter().any(|(v, _)| matches!(v, ViewerOpt::HelpView(_))) {
+            let entries = self.help_entries(db);
+            for (viewer, _) in self.viewers[self.current].iter_mut() {
+                if let ViewerOpt::HelpView(help) = viewer { help.set_entries(entries.clone()) }
+            }
         }
     }
-    // execute command for each plugin
-    // output execution result to result
-    fn execute(&mut self, db: &mut crate::DataBase) {
-        // decompose command to space-free arguments
-        let args = self.command.get().to_string();
-        let args = args.split_whitespace().collect::<Vec<&str>>();
-        // try execute internal commands
-        let res = EXES.try_execute(&args, self, db);
-        // decompose down the attributes of self
-        let Self { exeinfo, history, current, command, plugins, .. } = self;
-        // clear execution information
-        exeinfo.clear();
-        // capture the modified attributes
-        let mut handle_result  = |res| match res {
-            Ok(false) => {return false}
-            Err(e) => {*exeinfo = e; return true}
-            Ok(true) => {
-                // this might be filled by command
-                if exeinfo.is_empty() {
-                    *exeinfo = String::from("succeed: ") + command.get(); 
-                }
-                history[*current].push_str(command.get());
-                history[*current].push('\n');
-                return true
+    // execute the command line, expanding aliases on the way
+    // output execution result to exeinfo
+    fn execute(&mut self, line: &str, db: &mut crate::DataBase) {
+        self.exeinfo.clear();
+        if line.is_empty() { return }
+        // alias definitions take the raw line
+        let defined = match &self.config_err {
+            // aliases would be saved over a config.yaml that did not load
+            Some(e) => alias::define(&mut self.config.aliases.clone(), line)
+                .map(|_| Err(format!("aliases cannot be saved until the config loads: {e}"))),
+            None => alias::define(&mut self.config.aliases, line).map(|res| {
+                let path = format!("{}/config.yaml", crate::PATH);
+                res.and_then(|info| self.config.save_yaml(&path).map(|()| self.exeinfo = info).map_err(|e| e.to_string()))
+            }),
+        };
+        let res = match defined {
+            Some(res) => res,
+            // the whole line is one step for undo, and nothing of it is kept if it fails
+            None => db.atomic(|db| self.run_chain(line, db)),
+        };
+        match &res {
+            // this might be filled by commands
+            Ok(()) => if self.exeinfo.is_empty() {
+                self.exeinfo = String::from("succeed: ") + line;
+            },
+            Err(e) => self.exeinfo = e.clone(),
+        }
+        // every command is remembered with how it went, aliases in the commands they stand for
+        let ran = std::mem::take(&mut self.ran);
+        if ran.is_empty() { self.history.push(line, self.current, res.err()) }
+        for (command, err) in ran {
+            self.history.push(command, self.current, err);
+        }
+        let path = format!("{}/history.yaml", crate::PATH);
+        if let Err(e) = self.history.save_yaml(&path) {
+            self.exeinfo = format!("cannot save history: {e}");
+        }
+    }
+    // one viewer of a kind a page, set is applied to the one already there and new makes one for the whole page
+    // the corner is the first argument of the command, the viewer keeps its place unless it is given
+    fn open_viewer<V: Variant>(&mut self, args: &Args, set: impl FnOnce(&mut V) -> Result<(), String>,
+        new: impl FnOnce() -> Result<V, String>) -> Result<(), String> {
+        let (rows, cols) = self.layouts[self.current];
+        let corner = match args.get(0) {
+            Some(c) => Some(GridLayout::corner_from(c, rows, cols).ok_or_else(|| args.err(0, "is outside of the grid"))?),
+            None => None,
+        };
+        let viewers = &mut self.viewers[self.current];
+        match viewers.iter_mut().find_map(|(v, grid)| Some((V::of(v)?, grid))) {
+            Some((viewer, grid)) => {
+                set(viewer)?;
+                if corner.is_some() { *grid = corner }
             }
+            None => viewers.push((new()?.into(), corner.or(Some((0, rows * cols - 1))))),
+        }
+        Ok(())
+    }
+    // remove the viewer of a kind from this page, what names it in the error if there is none
+    fn close_viewer<V: Variant>(&mut self, what: &str) -> Result<(), String> {
+        let viewers = &mut self.viewers[self.current];
+        let len = viewers.len();
+        viewers.retain(|(v, _)| !V::is(v));
+        if len == viewers.len() { Err(format!("there is no {what} on this page"))? }
+        Ok(())
+    }
+    fn shift_calendar(&mut self, n: i32) -> Result<(), String> {
+        let cal = self.viewers[self.current].iter_mut().find_map(|(v, _)| match v {
+            ViewerOpt::CalView(cal) => Some(cal),
+            _ => None,
+        }).ok_or("there is no calendar on this page")?;
+        cal.set_span(cal.span().shift(n));
+        Ok(())
+    }
+    // the selected node of the tree on this page
+    fn tree_node(&self) -> Result<Node, String> {
+        self.viewers[self.current].iter().find_map(|(v, _)| match v {
+            ViewerOpt::TreeView(tree) => Some(tree.selected().ok_or_else(|| String::from("nothing is selected in the tree"))),
+            _ => None,
+        }).unwrap_or_else(|| Err(String::from("there is no tree on this page")))
+    }
+    fn planner(&mut self) -> Result<&mut PlanView, String> {
+        self.viewers[self.current].iter_mut().find_map(|(v, _)| match v {
+            ViewerOpt::PlanView(plan) => Some(plan),
+            _ => None,
+        }).ok_or_else(|| String::from("there is no planner on this page"))
+    }
+    // proposals of the planner by their numbers, all of them for all
+    fn proposals(&mut self, numbers: &[&str]) -> Result<Vec<(usize, crate::data::Proposal)>, String> {
+        let plan = self.planner()?;
+        if numbers.contains(&"all") {
+            return Ok(plan.proposals().cloned().enumerate().map(|(i, p)| (i + 1, p)).collect())
+        }
+        numbers.iter().map(|n| {
+            let n = n.parse::<usize>().map_err(|e| format!("{n}: {e}"))?;
+            plan.proposal(n).cloned().map(|p| (n, p)).ok_or_else(|| format!("#{n} is not proposed"))
+        }).collect()
+    }
+    // one editor a page with its plugin, a new one replaces the old one
+    // it takes the place and focus of the old one unless a corner is given
+    fn open_editor(&mut self, ed: EditorView, plug: EditorPlug, corner: Option<&str>) -> Result<(), String> {
+        let (rows, cols) = self.layouts[self.current];
+        let viewers = &mut self.viewers[self.current];
+        let old = viewers.iter().position(|(v, _)| matches!(v, ViewerOpt::EditorView(_)));
+        let corner = match corner {
+            Some(corner) => Some(GridLayout::corner_from(corner, rows, cols).ok_or("is outside of the grid")?),
+            None => old.and_then(|i| viewers[i].1).or(Some((0, rows * cols - 1))),
         };
-        // handle internal execution results
-        handle_result(res);
+        let focused = old.is_some() && old == self.focus;
+        if let Some(i) = old {
+            viewers.remove(i);
+            if self.focus > Some(i) { self.focus = self.focus.map(|f| f - 1) }
+        }
+        viewers.push((ed.into(), corner));
+        if focused { self.focus = Some(viewers.len() - 1) }
+        let plugins = &mut self.plugins[self.current];
+        plugins.retain(|p| !matches!(p, PluginOpt::EditorPlug(_)));
+        plugins.push(PluginOpt::EditorPlug(plug));
+        Ok(())
+    }
+    // run commands chained by ; and &&, a command after && is skipped if the previous one failed
+    // the first error is returned when the chain ends
+    fn run_chain(&mut self, line: &str, db: &mut crate::DataBase) -> Result<(), String> {
+        if self.nesting >= MAX_NESTING {
+            Err(format!("aliases and scripts nest deeper than {MAX_NESTING}, is there a loop?"))?
+        }
+        let chain = execute::chain(line)?;
+        let single = chain.len() == 1;
+        let mut first_err = None;
+        let mut ok = true;
+        self.nesting += 1;
+        for (and, command) in chain {
+            if and && !ok { continue }
+            let res = match alias::expand(&self.config.aliases, &command, db) {
+                Ok(Some(body)) => self.run_chain(&body, db),
+                Ok(None) => {
+                    let res = self.execute_one(&command, db);
+                    self.ran.push((command.clone(), res.clone().err()));
+                    res
+                }
+                Err(e) => {
+                    self.ran.push((command.clone(), Some(e.clone())));
+                    Err(e)
+                }
+            };
+            ok = res.is_ok();
+            if let Err(e) = res {
+                // tell which command of a chain failed
+                first_err.get_or_insert(if single { e } else { format!("{command}: {e}") });
+            }
+        }
+        self.nesting -= 1;
+        first_err.map_or(Ok(()), Err)
+    }
+    // run a script of commands line by line, stop at the first line that fails and undo the lines before it
+    // empty lines and lines starting with # are skipped
+    fn source(&mut self, path: &str, db: &mut crate::DataBase) -> Result<(), String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        // the history keeps the source command, not the lines of the script
+        let ran = std::mem::take(&mut self.ran);
+        let res = db.atomic(|db| {
+            for (i, line) in text.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') { continue }
+                self.run_chain(line, db).map_err(|e| format!("{path}:{}: {e}", i + 1))?;
+            }
+            Ok(())
+        });
+        self.ran = ran;
+        res
+    }
+    // execute one command, internal commands first and then plugins of current page
+    fn execute_one(&mut self, command: &str, db: &mut crate::DataBase) -> Result<(), String> {
+        // decompose command to arguments, quotes keep spaces in one argument
+        let args = execute::tokenize(command)?;
+        let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+        // try execute internal commands
+        if EXES.try_execute(&args, self, db)? { return Ok(()) }
         // try execute external commands
-        for plugin in plugins[*current].iter_mut() {
-            let res = plugin.try_execute(db, &args);
-            if handle_result(res) { break }
+        for plugin in self.plugins[self.current].iter_mut() {
+            if plugin.try_execute(db, &args)? { return Ok(()) }
         }
+        Err(format!("unknown command: {command}"))
     }
-    // generate internal prompts
+    // generate internal prompts from command patterns of internal commands and plugins
     fn int_prompts(&self, db: &crate::DataBase) -> String {
-        // FIXME: output meaningful prompts
-        let rands = "12344567890\nqwertyuiopasdfghjjklzxcvbnm ".chars().collect::<Vec<char>>();
-        String::from_iter((0..10000).map(|_| -> char { rands[rand::random::<usize>() % rands.len()] }))
+        let (rows, cols) = self.layouts[self.current];
+        let taken = self.viewers[self.current].iter()
+            .filter_map(|(_, grid)| *grid).collect::<Vec<_>>();
+        let slots = prompt::Slots::new(db, rows, cols, &taken, self.viewers.len());
+        let mut prompts = String::new();
+        let plugins = self.plugins[self.current].iter().flat_map(|p| p.commands());
+        for info in EXES.commands().into_iter().chain(plugins) {
+            prompt::generate(&info.pattern, &slots, &mut prompts);
+        }
+        prompts
+    }
+    // help entries of internal commands and plugins of the current page
+    // declared examples come first, then a few generated from real data
+    fn help_entries(&self, db: &crate::DataBase) -> Vec<HelpEntry> {
+        let (rows, cols) = self.layouts[self.current];
+        let taken = self.viewers[self.current].iter()
+            .filter_map(|(_, grid)| *grid).collect::<Vec<_>>();
+        let slots = prompt::Slots::new(db, rows, cols, &taken, self.viewers.len());
+        let internal = EXES.commands().into_iter().map(|info| (String::from("internal"), info));
+        let plugins = self.plugins[self.current].iter()
+            .flat_map(|p| p.commands().into_iter().map(|info| (p.name(), info)));
+        internal.chain(plugins).map(|(origin, info)| {
+            let mut generated = String::new();
+            prompt::generate(&info.pattern, &slots, &mut generated);
+            let mut examples = info.examples.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+            for line in generated.lines().filter(|l| !info.examples.contains(l)).take(2) {
+                examples.push(line.to_string());
+            }
+            HelpEntry::new(info, &origin, examples)
+        }).collect()
     }
     // list standby (not rendered, but already pulled) plugins and views
     // also render hidden tablets
@@ -374,62 +1088,69 @@ impl App {
     fn render_command(&self, f: &mut F, rect: Rect) {
         use tui::text::*;
         use tui::widgets::*;
+        use unicode_width::UnicodeWidthStr;
         let text = Text::from(self.window_content(rect.height as usize));
         f.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }), rect);
-        f.set_cursor(rect.x + self.command.xcursor() % rect.width, rect.y + self.command.xcursor() / rect.width);
+        // xcursor is a screen column, wide characters count twice
+        let xcursor = match &self.search {
+            // right after the query, before the found command
+            Some((query, skip)) => Self::search_head(&self.history, query, *skip).width() as u16,
+            None => self.command.xcursor(),
+        };
+        // a focused viewer shows its own cursor
+        if self.focus.is_none() {
+            f.set_cursor(rect.x + xcursor % rect.width, rect.y + xcursor / rect.width);
+        }
     }
-    // select prompts
-    fn select_prompts(&self) -> Vec<&str> {
-        use regex::*;
-        use unicode_width::UnicodeWidthStr;
-        fn count_matches<'a>(x: &'a str, regx: &Regex) -> Vec<(&'a str, usize)> {
-            let mut output = x.lines().filter_map(
-                |s: &str| -> Option<(&str, usize)> {
-                    let caps = regx.find_iter(s).fold(0, |a, b| a + b.len());
-                    (caps != 0).then_some((s, caps))
-                }
-            ).collect::<Vec<_>>();
-            output.sort_by(|a, b| (b.1*a.0.width()).cmp(&(a.1*b.0.width())));
-            return output;
+    // the command line while searching history, like readline
+    // it ends with `: ` and the found command
+    fn search_prompt(history: &history::History, query: &str, skip: usize) -> String {
+        let found = history.search(query, skip).map_or("", |found| found.command.as_str());
+        format!("{}': {found}", Self::search_head(history, query, skip))
+    }
+    // the search prompt up to the end of the query
+    fn search_head(history: &history::History, query: &str, skip: usize) -> String {
+        match history.search(query, skip) {
+            Some(_) => format!("(reverse-i-search)`{query}"),
+            None => format!("(failed reverse-i-search)`{query}"),
         }
-        let regex = self.command.get_regex();
-        let match_hist = count_matches(&self.history[self.current], &regex);
-        let match_prom = count_matches(&self.prompts, &regex);
-        let mut matches = match_hist.into_iter().map(|(s, _)| s).chain(match_prom.into_iter().map(|(s, _)| s)).collect::<Vec<_>>();
-        matches.dedup();
-        return matches;
     }
     // compute text highlighting from cursor position and given prompts
-    fn window_content(&self, height: usize) -> Vec<tui::text::Spans> {
+    // matched characters of prompts are highlighted
+    fn window_content(&self, height: usize) -> Vec<tui::text::Spans<'_>> {
         use tui::text::*;
         use tui::style::*;
-        // selected prompts and the original command
-        let prompts = self.select_prompts();
-        let command = self.command.get();
         // strong and faint colors
         let strong = Style::default().add_modifier(Modifier::BOLD);
         let normal = Style::default().fg(Color::Rgb(180, 180, 180));
-        if let Some(cursor) = self.ycursor {
-            if cursor + height - 1 < prompts.len() {
-                // if cursor is on prompts but not the bottom h - 1 ones
-                let window = prompts[cursor..cursor + height - 1].iter().copied();
-                [command].into_iter()
-                    .chain(window).enumerate()
-                    .map(|(i, s)| Span::styled(s, if i == 1 { strong } else { normal }))
-                    .map(Spans::from).collect()
-            } else {
-                // if cursor is on the bottom h - 1 elements
-                let window = prompts[prompts.len().max(height-1) + 1-height .. prompts.len()].iter().copied();
-                [command].into_iter()
-                    .chain(window).enumerate()
-                    .map(|(i, s)| Span::styled(s, if height.min(prompts.len()+1) - i == prompts.len() - cursor { strong } else { normal }))
-                    .map(Spans::from).collect()
+        let lit = Style::default().fg(Color::Rgb(255, 200, 80));
+        let len = self.completion.len();
+        let rows = height.saturating_sub(1).min(len);
+        // the window follows the cursor on prompts, but stops at the bottom
+        let start = match self.ycursor {
+            Some(cursor) if cursor + rows < len => cursor,
+            Some(_) => len - rows,
+            None => 0,
+        };
+        let command = match &self.search {
+            Some((query, skip)) => Span::styled(Self::search_prompt(&self.history, query, *skip), strong),
+            None => Span::styled(self.command.get().to_string(), if self.ycursor.is_none() { strong } else { normal }),
+        };
+        let mut lines = vec![Spans::from(command)];
+        for i in start..start + rows {
+            let text = self.completion.get(i).unwrap();
+            let style = if self.ycursor == Some(i) { strong } else { normal };
+            let mut spans = vec![];
+            let mut last = 0;
+            for &p in self.completion.positions(i) {
+                let end = p + text[p..].chars().next().map_or(0, char::len_utf8);
+                if p > last { spans.push(Span::styled(&text[last..p], style)) }
+                spans.push(Span::styled(&text[p..end], style.patch(lit)));
+                last = end;
             }
-        } else {
-            // if cursor is on command
-            let window = prompts[..(height-1).min(prompts.len())].iter().copied();
-            [Span::styled(command, strong)].into_iter()
-                .chain(window.map(|s| Span::styled(s, normal))).map(Spans::from).collect()
+            if last < text.len() { spans.push(Span::styled(&text[last..], style)) }
+            lines.push(Spans::from(spans));
         }
+        lines
     }
 }
@@ -1,3 +1,148 @@
+use serde::*;
+use crate::app::execute::{self, quote, Grammar, TryExecute};
+use crate::app::value;
+
+// commands on the item of the editor, attached with the editor viewer
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EditorPlug {
     id: usize,
-}
\ No newline at end of file
+    // an event if true, a project otherwise
+    ev: bool,
+}
+
+lazy_static::lazy_static!{
+    static ref PJ_EXES: Vec<execute::CommandExecution<EditorPlug>> = execute::x_decl! {
+        ("set name", "rename the edited project", ["set name \"house move\""],
+            w "^set$", w "^name$", v "^.+$" : "name" "new name", |this, args, db| {
+            db.pj_set_name(this.id, args[0].to_string()).map_err(|e| args.err(0, e))
+        })
+        ("set parent", "move the edited project under another parent",
+            w "^set$", w "^parent$|^pp$", v "^.+$" as Project : "parent" "new parent", |this, args, db| {
+            let pp = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            db.pj_set_pp(this.id, pp).map_err(|e| args.err(0, e))
+        })
+        ("set due", "set the deadline of the edited project, - removes it", ["set due 2026/11/01 17:00", "set due -"],
+            w "^set$", w "^due$|^deadline$", v r"^\d{4}/\d{2}/\d{2}$|^-$" as Date : "date" "YYYY/MM/DD or -",
+            o r"^\d{1,2}:\d{2}$" : "time" "HH:MM, the end of the day by default", |this, args, db| {
+            let due = match args[0] {
+                "-" => None,
+                date => Some(value::time(date, args.get(1), db).map_err(|e| args.err(0, e))?),
+            };
+            db.pj_set_deadline(this.id, due).map_err(|e| args.err(0, e))
+        })
+        ("set color", "set the color of the edited project", ["set color #ff8800"],
+            w "^set$", w "^color$|^colour$", v r"^#?[0-9a-fA-F]{6}$" as Color : "color" "hex rgb like ff8800", |this, args, db| {
+            db.pj_set_color(this.id, args.parse_with(0, value::color)?).map_err(|e| args.err(0, e))
+        })
+        ("set weight", "set the weight of the edited project, and whether its time is flexible or reserved", ["set weight 3 flexible"],
+            w "^set$", w "^weight$", v r"^\d+$" : "weight" "relative share of planned time",
+            o "^flex$|^flexible$|^rsv$|^reserved$" as Choice : "type" "flexible or reserved, unchanged by default", |this, args, db| {
+            db.pj_set_weight(this.id, args.parse(0)?).map_err(|e| args.err(0, e))?;
+            if args.get(1).is_some() {
+                db.pj_set_weight_type(this.id, args.parse_with(1, value::weight_type)?).map_err(|e| args.err(1, e))?;
+            }
+            Ok(())
+        })
+        ("set quota", "set the estimated quota of the edited project in hours, and the quota done if given", ["set quota 12 5"],
+            w "^set$", w "^quota$", v r"^\d+$" : "estimated" "hours the project takes", o r"^\d+$" : "done" "hours already spent", |this, args, db| {
+            db.pj_set_quota_esti(this.id, args.parse(0)?).map_err(|e| args.err(0, e))?;
+            if args.get(1).is_some() {
+                db.pj_set_quota_done(this.id, args.parse(1)?).map_err(|e| args.err(1, e))?;
+            }
+            Ok(())
+        })
+        // dependencies are prompted by ext_prompts, only peers can be added
+        ("add dep", "make the edited project start after others of the same parent", ["add dep packing"],
+            w "^add$", w "^dep$|^deps$", m "^.+$" : "dependency" "projects it waits for", |this, args, db| {
+            for dep in args.all(0) {
+                value::pj_id(db, dep).and_then(|d| db.pj_add_deps(this.id, d).map_err(|e| e.to_string()))
+                    .map_err(|e| format!("{dep}: {e}"))?;
+            }
+            Ok(())
+        })
+        ("rm dep", "remove dependencies of the edited project", ["rm dep packing"],
+            w "^rm$|^del$", w "^dep$|^deps$", m "^.+$" : "dependency" "projects it no longer waits for", |this, args, db| {
+            for dep in args.all(0) {
+                value::pj_id(db, dep).and_then(|d| db.pj_rmv_deps(this.id, d).map_err(|e| e.to_string()))
+                    .map_err(|e| format!("{dep}: {e}"))?;
+            }
+            Ok(())
+        })
+    };
+    static ref EV_EXES: Vec<execute::CommandExecution<EditorPlug>> = execute::x_decl! {
+        ("set name", "rename the edited event", ["set name \"pack books\""],
+            w "^set$", w "^name$", v "^.+$" : "name" "new name", |this, args, db| {
+            db.ev_set_name(this.id, args[0].to_string()).map_err(|e| args.err(0, e))
+        })
+        ("set parent", "move the edited event to another project",
+            w "^set$", w "^parent$|^pp$", v "^.+$" as Project : "parent" "new parent", |this, args, db| {
+            let pp = value::pj_id(db, args[0]).map_err(|e| args.err(0, e))?;
+            db.ev_set_pp(this.id, pp).map_err(|e| args.err(0, e))
+        })
+        ("set time", "move the edited event to another time", ["set time 2026/10/21 09:00"],
+            w "^set$", w "^time$", v r"^\d{4}/\d{2}/\d{2}$" as Date : "date" "YYYY/MM/DD", v r"^\d{1,2}:\d{2}$" : "time" "HH:MM", |this, args, db| {
+            let time = value::time(args[0], Some(args[1]), db).map_err(|e| args.err(0, e))?;
+            db.ev_set_time(this.id, time).map_err(|e| args.err(0, e))
+        })
+        ("set quota", "set the estimated quota of the edited event in hours", ["set quota 3"],
+            w "^set$", w "^quota$", v r"^\d+$" : "estimated" "hours the event takes", |this, args, db| {
+            db.ev_set_quota_esti(this.id, args.parse(0)?).map_err(|e| args.err(0, e))
+        })
+    };
+}
+
+impl EditorPlug {
+    pub fn pj(id: usize) -> Self {
+        EditorPlug { id, ev: false }
+    }
+    pub fn ev(id: usize) -> Self {
+        EditorPlug { id, ev: true }
+    }
+    fn exes(&self) -> &'static Vec<execute::CommandExecution<EditorPlug>> {
+        if self.ev { &EV_EXES } else { &PJ_EXES }
+    }
+    // local time like 2026/11/01 17:00
+    fn time(db: &crate::DataBase, time: i64) -> String {
+        chrono::NaiveDateTime::from_timestamp_opt(time + db.tz() as i64 * 60 * 60, 0)
+            .unwrap().format("%Y/%m/%d %H:%M").to_string()
+    }
+}
+
+impl super::Plugin for EditorPlug {
+    fn name(&self) -> String {
+        "editor".to_string()
+    }
+    fn commands(&self) -> Vec<&execute::CommandInfo> {
+        self.exes().commands()
+    }
+    // current values to start editing from, and the dependencies that can be added or removed
+    fn ext_prompts(&self, db: &crate::DataBase, prompts: &mut String) {
+        let mut lines = vec![];
+        if self.ev {
+            let Some(ev) = db.ev_get_by_id(self.id) else { return };
+            lines.push(format!("set name {}", quote(ev.name())));
+            lines.push(format!("set time {}", Self::time(db, ev.time())));
+            lines.push(format!("set quota {}", ev.quota_esti()));
+        } else {
+            let Some(pj) = db.pj_get_by_id(self.id) else { return };
+            lines.push(format!("set name {}", quote(pj.name())));
+            if let Some(due) = pj.deadline() { lines.push(format!("set due {}", Self::time(db, due))) }
+            lines.push(format!("set color #{:06x}", pj.color_usize()));
+            lines.push(format!("set quota {} {}", pj.quota_esti(), pj.quota_done()));
+            let deps = pj.iter_deps().collect::<Vec<_>>();
+            let mut peers = db.pj_get_by_id(pj.pp()).map(|pp| pp.iter_chpj().collect::<Vec<_>>()).unwrap_or_default();
+            peers.sort();
+            for peer in peers.into_iter().filter(|&p| p != pj.id()) {
+                let name = quote(db.pj_get_by_id(peer).unwrap().name());
+                lines.push(if deps.contains(&peer) { format!("rm dep {name}") } else { format!("add dep {name}") });
+            }
+        }
+        for line in lines {
+            prompts.push_str(&line);
+            prompts.push('\n');
+        }
+    }
+    fn try_execute(&mut self, db: &mut crate::DataBase, command: &Vec<&str>) -> Result<bool, String> {
+        self.exes().try_execute(command, self, db)
+    }
+}
@@ -1,10 +1,13 @@
 //! command line plugins
 use serde::*;
 mod editor;
+pub use editor::*;
 
 pub trait Plugin {
     // get name
     fn name(&self) -> String;
+    // commands of this plugin, prompts and help are generated from them
+    fn commands(&self) -> Vec<&super::execute::CommandInfo>;
     // extend prompts with this plugin
     fn ext_prompts(&self, db: &crate::DataBase, prompts: &mut String);
     // try to execute a command, if this command is matched by this plugin, return true
@@ -14,6 +17,7 @@ pub trait Plugin {
 #[derive(Debug, Serialize, Deserialize)]
 pub enum PluginOpt {
     Null,
+    EditorPlug(EditorPlug),
 }
 
 impl Plugin for PluginOpt {
@@ -22,6 +26,15 @@ impl Plugin for PluginOpt {
         use PluginOpt::*;
         match self {
             Null => format!("null"),
+            EditorPlug(x) => x.name(),
+        }
+    }
+    // commands
+    fn commands(&self) -> Vec<&super::execute::CommandInfo> {
+        use PluginOpt::*;
+        match self {
+            Null => vec![],
+            EditorPlug(x) => x.commands(),
         }
     }
     // extend prompts
@@ -29,6 +42,7 @@ impl Plugin for PluginOpt {
         use PluginOpt::*;
         match self {
             Null => {},
+            EditorPlug(x) => x.ext_prompts(db, prompts),
         }
     }
     // try execute a command
@@ -36,6 +50,7 @@ impl Plugin for PluginOpt {
         use PluginOpt::*;
         match self {
             Null => Ok(false),
+            EditorPlug(x) => x.try_execute(db, command),
         }
     }
 }
\ No newline at end of file
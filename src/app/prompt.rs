@@ -0,0 +1,98 @@
+//! prompts generated from command patterns
+use super::execute::{alternatives, quote, ArgPattern, Slot};
+
+// maximal number of prompts generated from one pattern list
+const MAX_PER_PATTERN: usize = 512;
+
+// real values for each kind of slot
+pub struct Slots {
+    project: Vec<String>,
+    event: Vec<String>,
+    corner: Vec<String>,
+    page: Vec<String>,
+    date: Vec<String>,
+}
+
+impl Slots {
+    // taken: corners of viewers already on the page
+    pub fn new(db: &crate::DataBase, rows: u16, cols: u16, taken: &[(u16, u16)], pages: usize) -> Self {
+        use chrono::Duration;
+        let mut project = db.pj_list().unwrap_or_default();
+        project.sort();
+        let mut event = db.ev_list().unwrap_or_default();
+        event.sort();
+        // a corner is free if none of its cells is covered by a viewer
+        let covered = |r: u16, c: u16| taken.iter().any(|&(lu, rd)| {
+            (lu / cols..=rd / cols).contains(&r) && (lu % cols..=rd % cols).contains(&c)
+        });
+        let mut corner = vec![];
+        for (r0, c0) in (0..rows).flat_map(|r| (0..cols).map(move |c| (r, c))) {
+            for (r1, c1) in (r0..rows).flat_map(|r| (c0..cols).map(move |c| (r, c))) {
+                let free = (r0..=r1).all(|r| (c0..=c1).all(|c| !covered(r, c)));
+                if free { corner.push(format!("{r0}{c0}:{r1}{c1}")) }
+            }
+        }
+        // larger areas first
+        corner.sort_by_key(|s| {
+            let b = s.as_bytes();
+            std::cmp::Reverse((b[3] - b[0] + 1) as u16 * (b[4] - b[1] + 1) as u16)
+        });
+        let today = db.datetime_loc().unwrap().date();
+        let date = (0..14).map(|d| (today + Duration::days(d)).format("%Y/%m/%d").to_string()).collect();
+        let page = (0..pages).map(|p| p.to_string()).collect();
+        Slots { project, event, corner, page, date }
+    }
+    // none if the slot cannot be filled with real data
+    fn values(&self, regex: &regex::Regex, slot: Slot) -> Option<Vec<String>> {
+        let values = match slot {
+            Slot::Text => None?,
+            Slot::Choice => alternatives(regex),
+            Slot::Project => self.project.clone(),
+            Slot::Event => self.event.clone(),
+            Slot::Corner => self.corner.clone(),
+            Slot::Grid => (1..=4).flat_map(|r| (1..=4).map(move |c| format!("{r}{c}"))).collect(),
+            Slot::Page => self.page.clone(),
+            Slot::Date => self.date.clone(),
+            Slot::Color => ["ff0000", "ff8800", "ffff00", "00ff00", "00ffff", "0000ff", "ff00ff", "ffffff"]
+                .into_iter().map(String::from).collect(),
+        };
+        // names with spaces are quoted to stay one argument
+        Some(values.into_iter().filter(|v| regex.is_match(v)).map(|v| quote(&v)).collect())
+    }
+}
+
+// append one line for each way to fill the patterns
+pub fn generate(pattern: &[ArgPattern], slots: &Slots, out: &mut String) {
+    let mut choices = vec![];
+    for arg in pattern {
+        let values = match arg {
+            ArgPattern::Word(r) => alternatives(r).into_iter().take(1).collect(),
+            ArgPattern::Variable(r, slot) | ArgPattern::Repeated(r, slot) => match slots.values(r, *slot) {
+                Some(values) => values,
+                None => return,
+            },
+            // optional arguments are first left out
+            ArgPattern::Optional(r, slot, _) => {
+                let values = slots.values(r, *slot).unwrap_or_default();
+                [String::new()].into_iter().chain(values).collect()
+            }
+            ArgPattern::Keyword(name, r, slot, _) => {
+                let values = slots.values(r, *slot).unwrap_or_default();
+                [String::new()].into_iter().chain(values.into_iter().map(|v| format!("--{name}={v}"))).collect()
+            }
+        };
+        if values.is_empty() { return }
+        choices.push(values);
+    }
+    // walk the cartesian product like an odometer, last argument changes fastest
+    let mut index = vec![0usize; choices.len()];
+    for _ in 0..MAX_PER_PATTERN {
+        let line = index.iter().zip(choices.iter())
+            .map(|(&i, c)| c[i].as_str()).filter(|s| !s.is_empty()).collect::<Vec<_>>();
+        out.push_str(&line.join(" "));
+        out.push('\n');
+        let Some(k) = (0..choices.len()).rev().find(|&k| index[k] + 1 < choices[k].len()) else { return };
+        index[k] += 1;
+        for i in index.iter_mut().skip(k + 1) { *i = 0 }
+    }
+}
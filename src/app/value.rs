@@ -0,0 +1,45 @@
+//! values typed on the command line, shared by commands and plugins
+use crate::data::*;
+
+// id of a project by its name
+pub fn pj_id(db: &DataBase, name: &str) -> Result<usize, String> {
+    db.pj_get_by_name(name).map(|pj| pj.id()).ok_or_else(|| format!("no project is named {name}"))
+}
+
+// id of an event by its name
+pub fn ev_id(db: &DataBase, name: &str) -> Result<usize, String> {
+    db.ev_get_by_name(name).map(|ev| ev.id()).ok_or_else(|| format!("no event is named {name}"))
+}
+
+// local YYYY/MM/DD and an optional HH:MM, the day ends at 23:59 if no time is given
+pub fn local(date: &str, clock: Option<&str>) -> Result<chrono::NaiveDateTime, String> {
+    let date = chrono::NaiveDate::parse_from_str(date, "%Y/%m/%d")
+        .map_err(|_| format!("invalid date {date}, use YYYY/MM/DD"))?;
+    let clock = match clock {
+        Some(clock) => chrono::NaiveTime::parse_from_str(clock, "%H:%M")
+            .map_err(|_| format!("invalid time {clock}, use HH:MM"))?,
+        None => chrono::NaiveTime::from_hms_opt(23, 59, 0).unwrap(),
+    };
+    Ok(date.and_time(clock))
+}
+
+// local time to utc seconds
+pub fn time(date: &str, clock: Option<&str>, db: &DataBase) -> Result<i64, String> {
+    Ok(local(date, clock)?.timestamp() - db.tz() as i64 * 60 * 60)
+}
+
+// hex rgb like ff8800 or #ff8800
+pub fn color(hex: &str) -> Result<(u8, u8, u8), String> {
+    let digits = hex.trim_start_matches('#');
+    let rgb = u32::from_str_radix(digits, 16).ok().filter(|_| digits.len() == 6)
+        .ok_or_else(|| format!("invalid color {hex}, use hex rgb like ff8800"))?;
+    Ok(((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+pub fn weight_type(s: &str) -> Result<WeightType, String> {
+    match s {
+        "flex" | "flexible" => Ok(WeightType::Flexible),
+        "rsv" | "reserved" => Ok(WeightType::Reserved),
+        _ => Err(format!("invalid weight type {s}, use flexible or reserved")),
+    }
+}
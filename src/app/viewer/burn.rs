@@ -0,0 +1,179 @@
+use chrono::NaiveDateTime;
+use tui::style::{Color, Style};
+use crate::data::*;
+use super::Viewer;
+use serde::*;
+
+const DAY: f64 = 24.0 * 60.0 * 60.0;
+
+// what the chart follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Burn {
+    // hours left going down to none
+    Down,
+    // hours done going up to the estimate
+    Up,
+}
+
+impl std::str::FromStr for Burn {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "down" => Ok(Burn::Down),
+            "up" => Ok(Burn::Up),
+            _ => Err(format!("invalid chart {s}, use down or up")),
+        }
+    }
+}
+
+impl std::fmt::Display for Burn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Burn::Down => "down",
+            Burn::Up => "up",
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BurnView {
+    root: usize,
+    burn: Burn,
+    #[serde(skip)]
+    name: String,
+    #[serde(skip)]
+    color: Option<Color>,
+    // quota of the whole subtree after each change, oldest first
+    #[serde(skip)]
+    changes: Vec<QuotaChange>,
+    #[serde(skip)]
+    deadline: Option<i64>,
+    #[serde(skip)]
+    tz: i64,
+    #[serde(skip)]
+    now: i64,
+}
+
+impl BurnView {
+    pub fn new(root: usize, burn: Burn) -> Self {
+        BurnView { root, burn, name: String::new(), color: None, changes: vec![], deadline: None, tz: 0, now: 0 }
+    }
+    pub fn set(&mut self, root: usize, burn: Burn) {
+        self.root = root;
+        self.burn = burn;
+    }
+    // the project and the projects below it
+    fn walk(db: &DataBase, id: usize, ids: &mut Vec<usize>) {
+        let Some(pj) = db.pj_get_by_id(id) else { return };
+        if id != 0 { ids.push(id) }
+        for ch in pj.iter_chpj() {
+            Self::walk(db, ch, ids);
+        }
+    }
+    // the first and last times charted, from the first known change to the deadline or now
+    fn span(&self) -> (i64, i64) {
+        let start = self.changes.iter().map(|c| c.time).find(|&t| t > 0).unwrap_or(self.now).min(self.now);
+        let end = self.deadline.unwrap_or(self.now).max(self.now);
+        (start, end.max(start + DAY as i64))
+    }
+    // points of a value over time, held until the next change and up to now
+    fn steps(&self, start: i64, value: impl Fn(&QuotaChange) -> usize) -> Vec<(f64, f64)> {
+        let x = |t: i64| (t.max(start) - start) as f64 / DAY;
+        let mut points: Vec<(f64, f64)> = vec![];
+        for c in self.changes.iter() {
+            if let Some(&(_, last)) = points.last() { points.push((x(c.time), last)) }
+            points.push((x(c.time), value(c) as f64));
+        }
+        if let Some(&(_, last)) = points.last() { points.push((x(self.now), last)) }
+        points
+    }
+}
+
+impl Viewer for BurnView {
+    fn name(&self) -> String {
+        "burn".to_string()
+    }
+    fn refresh(&mut self, db: &crate::DataBase) {
+        // a deleted root falls back to the root of everything
+        if db.pj_get_by_id(self.root).is_none() { self.root = 0 }
+        let Some(root) = db.pj_get_by_id(self.root) else { return };
+        self.name = root.name().to_string();
+        self.color = match root.color_usize() {
+            0 => None,
+            c => Some(Color::Rgb((c >> 16) as u8, (c >> 8) as u8, c as u8)),
+        };
+        self.tz = db.tz() as i64 * 60 * 60;
+        self.now = chrono::Utc::now().timestamp();
+        let mut ids = vec![];
+        Self::walk(db, self.root, &mut ids);
+        // the deadline of the project, or the last one below it
+        self.deadline = root.deadline().or_else(|| ids.iter().filter_map(|&id| db.pj_get_by_id(id)?.deadline()).max());
+        let logs = ids.iter().map(|&id| db.pj_quota_log(id)).collect::<Vec<_>>();
+        let mut times = logs.iter().flatten().map(|c| c.time).collect::<Vec<_>>();
+        times.sort();
+        times.dedup();
+        // a project counts from its first change on, with what it had then
+        self.changes = times.into_iter().map(|time| {
+            let mut total = QuotaChange { time, esti: 0, done: 0 };
+            for c in logs.iter().filter_map(|log| log.iter().take_while(|c| c.time <= time).last()) {
+                total.esti += c.esti;
+                total.done += c.done.min(c.esti);
+            }
+            total
+        }).collect();
+        self.changes.dedup_by_key(|c| (c.esti, c.done));
+    }
+    fn render(&self, f: &mut super::Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        use tui::symbols::Marker;
+        use tui::text::*;
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let red = Style::default().fg(Color::Rgb(255, 90, 90));
+        let line = Style::default().fg(self.color.unwrap_or(Color::Rgb(255, 200, 60)));
+        let (start, end) = self.span();
+        let x = |t: i64| (t.max(start) - start) as f64 / DAY;
+        let last = self.changes.last().copied().unwrap_or(QuotaChange { time: 0, esti: 0, done: 0 });
+        let top = (self.changes.iter().map(|c| c.esti).max().unwrap_or(0) as f64 * 1.1).ceil().max(1.0);
+        // the ideal runs from when there was work estimated to the deadline
+        let begun = self.changes.iter().find(|c| c.esti > 0);
+        let (actual, ideal, scope) = match self.burn {
+            Burn::Down => (
+                self.steps(start, |c| c.esti - c.done),
+                begun.zip(self.deadline).map(|(c, d)| vec![(x(c.time), (c.esti - c.done) as f64), (x(d), 0.0)]),
+                None,
+            ),
+            Burn::Up => (
+                self.steps(start, |c| c.done),
+                begun.zip(self.deadline).map(|(c, d)| vec![(x(c.time), c.done as f64), (x(d), last.esti as f64)]),
+                Some(self.steps(start, |c| c.esti)),
+            ),
+        };
+        let today = vec![(x(self.now), 0.0), (x(self.now), top)];
+        let mut datasets = vec![];
+        if let Some(ideal) = ideal.as_ref() {
+            datasets.push(Dataset::default().name("ideal").marker(Marker::Braille).graph_type(GraphType::Line).style(faint).data(ideal));
+        }
+        if let Some(scope) = scope.as_ref() {
+            datasets.push(Dataset::default().name("estimated").marker(Marker::Braille).graph_type(GraphType::Line).style(faint).data(scope));
+        }
+        let name = match self.burn { Burn::Down => "left", Burn::Up => "done" };
+        datasets.push(Dataset::default().name(name).marker(Marker::Braille).graph_type(GraphType::Line).style(line).data(&actual));
+        datasets.push(Dataset::default().name("today").marker(Marker::Braille).graph_type(GraphType::Line).style(red).data(&today));
+        let date = |t: i64| NaiveDateTime::from_timestamp_opt(t + self.tz, 0).unwrap().format("%m/%d").to_string();
+        let x_labels = [start, start + (end - start) / 2, end].map(|t| Span::styled(date(t), faint)).to_vec();
+        let y_labels = [0.0, top / 2.0, top].map(|y| Span::styled(format!("{y:.0}h"), faint)).to_vec();
+        let mut title = match self.burn {
+            Burn::Down => format!("burndown of {}, {}h left", self.name, last.esti - last.done),
+            Burn::Up => format!("burnup of {}, {}/{}h done", self.name, last.done, last.esti),
+        };
+        match self.deadline {
+            Some(d) => title.push_str(&format!(", due {}", date(d))),
+            None => title.push_str(", no deadline"),
+        }
+        let chart = Chart::new(datasets)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .x_axis(Axis::default().bounds([0.0, x(end)]).labels(x_labels))
+            .y_axis(Axis::default().bounds([0.0, top]).labels(y_labels));
+        f.render_widget(chart, rect);
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::style::Color;
+use crate::config::WeekStart;
+use super::{KeyResult, Viewer};
+use serde::*;
+
+// width and height of a month in the year view
+const MINI_W: u16 = 22;
+const MINI_H: u16 = 8;
+
+// what the calendar shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CalSpan {
+    // year and month from 1
+    Month(i32, u32),
+    Year(i32),
+}
+
+impl CalSpan {
+    // the span moved by n months or years
+    pub fn shift(self, n: i32) -> Self {
+        match self {
+            CalSpan::Month(y, m) => {
+                let i = y * 12 + m as i32 - 1 + n;
+                CalSpan::Month(i.div_euclid(12), i.rem_euclid(12) as u32 + 1)
+            }
+            CalSpan::Year(y) => CalSpan::Year(y + n),
+        }
+    }
+    // the span of the same kind holding a date
+    fn around(self, date: NaiveDate) -> Self {
+        match self {
+            CalSpan::Month(..) => CalSpan::Month(date.year(), date.month()),
+            CalSpan::Year(_) => CalSpan::Year(date.year()),
+        }
+    }
+}
+
+// what happens on a local day
+#[derive(Debug, Clone, Default)]
+struct Day {
+    // events by time, like (09:00, name, color)
+    evs: Vec<(String, String, Color)>,
+    // projects due this day
+    dues: Vec<(String, Color)>,
+    // estimated hours of the events
+    quota: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalView {
+    span: CalSpan,
+    // set by the app from the config
+    #[serde(skip)]
+    week_start: WeekStart,
+    #[serde(skip)]
+    days: BTreeMap<NaiveDate, Day>,
+    #[serde(skip)]
+    today: Option<NaiveDate>,
+    #[serde(skip)]
+    focused: bool,
+}
+
+impl CalView {
+    pub fn new(span: CalSpan) -> Self {
+        CalView { span, week_start: WeekStart::default(), days: BTreeMap::new(), today: None, focused: false }
+    }
+    pub fn set_span(&mut self, span: CalSpan) {
+        self.span = span;
+    }
+    pub fn span(&self) -> CalSpan {
+        self.span
+    }
+    // the day a week starting on week_start begins, on or before a date
+    fn week_of(&self, date: NaiveDate) -> NaiveDate {
+        let back = (date.weekday().num_days_from_monday() + 7 - self.week_start.weekday().num_days_from_monday()) % 7;
+        date - Duration::days(back as i64)
+    }
+    // weekdays from the start of the week
+    fn weekdays(&self) -> impl Iterator<Item = Weekday> {
+        let mut day = self.week_start.weekday();
+        (0..7).map(move |_| { let d = day; day = day.succ(); d })
+    }
+    fn month_name(year: i32, month: u32) -> String {
+        NaiveDate::from_ymd_opt(year, month, 1).unwrap().format("%B %Y").to_string()
+    }
+    // background of a day in the year view, brighter with more hours of events
+    fn shade(quota: usize) -> Option<Color> {
+        match quota {
+            0 => None,
+            1 => Some(Color::Rgb(30, 70, 40)),
+            2..=3 => Some(Color::Rgb(40, 110, 55)),
+            4..=5 => Some(Color::Rgb(50, 150, 70)),
+            _ => Some(Color::Rgb(60, 190, 85)),
+        }
+    }
+    fn render_month(&self, f: &mut super::Frame, rect: tui::layout::Rect, year: i32, month: u32) {
+        use tui::widgets::*;
+        use tui::style::*;
+        use tui::text::*;
+        use tui::layout::*;
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let due = Style::default().fg(Color::Rgb(255, 90, 90)).add_modifier(Modifier::BOLD);
+        // one header line and six weeks, so every month has the same shape
+        let (w, h) = (rect.width / 7, rect.height.saturating_sub(1) / 6);
+        if w == 0 || h == 0 { return }
+        for (i, day) in self.weekdays().enumerate() {
+            let cell = Rect { x: rect.x + i as u16 * w, y: rect.y, width: w, height: 1 };
+            f.render_widget(Paragraph::new(Span::styled(day.to_string(), faint)), cell);
+        }
+        let first = self.week_of(NaiveDate::from_ymd_opt(year, month, 1).unwrap());
+        for i in 0..42 {
+            let date = first + Duration::days(i);
+            let cell = Rect { x: rect.x + (i % 7) as u16 * w, y: rect.y + 1 + (i / 7) as u16 * h, width: w, height: h };
+            let day = self.days.get(&date);
+            let mut number = if date.month() == month { Style::default() } else { faint };
+            if day.is_some_and(|d| !d.dues.is_empty()) { number = number.patch(due) }
+            if Some(date) == self.today { number = number.add_modifier(Modifier::REVERSED) }
+            let mut lines = vec![Spans::from(Span::styled(format!("{:>2}", date.day()), number))];
+            if let Some(day) = day {
+                let mut items = day.dues.iter().map(|(name, color)| Spans::from(vec![
+                    Span::styled("!", Style::default().fg(*color)),
+                    Span::raw(name.clone()),
+                ])).chain(day.evs.iter().map(|(time, name, color)| Spans::from(vec![
+                    Span::styled("●", Style::default().fg(*color)),
+                    Span::styled(time.clone(), faint),
+                    Span::raw(format!(" {name}")),
+                ]))).collect::<Vec<_>>();
+                // what does not fit is counted, next to the day if nothing fits
+                let room = h as usize - 1;
+                if items.len() > room {
+                    let keep = room.saturating_sub(1);
+                    let more = Span::styled(format!(" +{}", items.len() - keep), faint);
+                    items.truncate(keep);
+                    match room {
+                        0 => lines[0].0.push(more),
+                        _ => items.push(Spans::from(more)),
+                    }
+                }
+                lines.extend(items);
+            }
+            f.render_widget(Paragraph::new(lines), cell);
+        }
+    }
+    fn render_year(&self, f: &mut super::Frame, rect: tui::layout::Rect, year: i32) {
+        use tui::widgets::*;
+        use tui::style::*;
+        use tui::text::*;
+        use tui::layout::*;
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let due = Style::default().fg(Color::Rgb(255, 90, 90)).add_modifier(Modifier::BOLD);
+        // the widest arrangement that fits, months left over are not drawn
+        let cols = [6, 4, 3, 2, 1].into_iter()
+            .find(|&c| c * MINI_W <= rect.width && 12 / c * MINI_H <= rect.height)
+            .or_else(|| [6, 4, 3, 2, 1].into_iter().find(|&c| c * MINI_W <= rect.width)).unwrap_or(1);
+        let header = self.weekdays().map(|d| d.to_string()[..2].to_string()).collect::<Vec<_>>().join(" ");
+        for month in 1..=12u32 {
+            let (r, c) = ((month as u16 - 1) / cols, (month as u16 - 1) % cols);
+            let cell = Rect { x: rect.x + c * MINI_W, y: rect.y + r * MINI_H, width: MINI_W, height: MINI_H };
+            if cell.bottom() > rect.bottom() || cell.right() > rect.right() { continue }
+            let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+            let mut lines = vec![
+                Spans::from(Span::styled(first.format("%B").to_string(), Style::default().add_modifier(Modifier::BOLD))),
+                Spans::from(Span::styled(header.clone(), faint)),
+            ];
+            let start = self.week_of(first);
+            for week in 0..6 {
+                let mut spans = vec![];
+                for d in 0..7 {
+                    let date = start + Duration::days(week * 7 + d);
+                    if date.month() != month {
+                        spans.push(Span::raw("   "));
+                        continue
+                    }
+                    let day = self.days.get(&date);
+                    let mut style = Style { bg: Self::shade(day.map_or(0, |d| d.quota)), ..Default::default() };
+                    if day.is_some_and(|d| !d.dues.is_empty()) { style = style.patch(due) }
+                    if Some(date) == self.today { style = style.add_modifier(Modifier::REVERSED) }
+                    spans.push(Span::styled(format!("{:>2}", date.day()), style));
+                    spans.push(Span::raw(" "));
+                }
+                lines.push(Spans::from(spans));
+            }
+            f.render_widget(Paragraph::new(lines), cell);
+        }
+    }
+}
+
+impl Viewer for CalView {
+    fn name(&self) -> String {
+        "calendar".to_string()
+    }
+    fn set_config(&mut self, config: &crate::config::Config) {
+        self.week_start = config.week_start;
+    }
+    fn refresh(&mut self, db: &crate::DataBase) {
+        // days are local, so events near midnight land where the user expects them
+        let tz = db.tz() as i64 * 60 * 60;
+        let local = |t: i64| NaiveDateTime::from_timestamp_opt(t + tz, 0).unwrap();
+        self.days.clear();
+        for ev in db.ev_list().unwrap_or_default().iter().filter_map(|n| db.ev_get_by_name(n)) {
+            let time = local(ev.time());
+            let day = self.days.entry(time.date()).or_default();
+            day.evs.push((time.format("%H:%M").to_string(), ev.name().to_string(), ev.color_rgb()));
+            day.quota += ev.quota_esti();
+        }
+        for pj in db.pj_list().unwrap_or_default().iter().filter_map(|n| db.pj_get_by_name(n)) {
+            let Some(due) = pj.deadline() else { continue };
+            self.days.entry(local(due).date()).or_default().dues.push((pj.name().to_string(), pj.color_rgb()));
+        }
+        for day in self.days.values_mut() {
+            day.evs.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+            day.dues.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        self.today = db.datetime_loc().ok().map(|t| t.date());
+    }
+    fn focus(&mut self, on: bool) -> bool {
+        self.focused = on;
+        true
+    }
+    fn key(&mut self, key: KeyEvent) -> KeyResult {
+        match key.code {
+            KeyCode::Left | KeyCode::PageUp => self.span = self.span.shift(-1),
+            KeyCode::Right | KeyCode::PageDown => self.span = self.span.shift(1),
+            KeyCode::Home => match self.today {
+                Some(today) => self.span = self.span.around(today),
+                None => return KeyResult::Ignored,
+            },
+            _ => return KeyResult::Ignored,
+        }
+        KeyResult::Consumed
+    }
+    fn render(&self, f: &mut super::Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        use tui::style::*;
+        let mut title = match self.span {
+            CalSpan::Month(y, m) => Self::month_name(y, m),
+            CalSpan::Year(y) => y.to_string(),
+        };
+        if self.focused { title.push_str(", left/right to move, esc to leave") }
+        let mut border = Style::default();
+        if self.focused { border = border.add_modifier(Modifier::BOLD) }
+        let block = Block::default().borders(Borders::ALL).title(title).border_style(border);
+        let inner = block.inner(rect);
+        f.render_widget(block, rect);
+        match self.span {
+            CalSpan::Month(y, m) => self.render_month(f, inner, y, m),
+            CalSpan::Year(y) => self.render_year(f, inner, y),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use unicode_width::UnicodeWidthChar;
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+
+// directions a line leaves a cell to
+pub const UP: u8 = 1;
+pub const DOWN: u8 = 2;
+pub const LEFT: u8 = 4;
+pub const RIGHT: u8 = 8;
+
+// characters drawn so far, lines are kept as directions to join them when they cross
+#[derive(Default)]
+pub struct Canvas {
+    cells: HashMap<(usize, usize), (Option<char>, u8, Style)>,
+}
+
+impl Canvas {
+    pub fn line(&mut self, x: usize, y: usize, dirs: u8, style: Style) {
+        let cell = self.cells.entry((x, y)).or_insert((None, 0, style));
+        cell.1 |= dirs;
+        // emphasized lines stay emphasized where they cross others
+        if style.add_modifier.contains(Modifier::BOLD) || cell.1 == dirs { cell.2 = style }
+    }
+    pub fn text(&mut self, x: usize, y: usize, text: &str, style: Style) {
+        let mut x = x;
+        for c in text.chars() {
+            self.cells.insert((x, y), (Some(c), 0, style));
+            x += c.width().unwrap_or(0);
+        }
+    }
+    pub fn get(&self, x: usize, y: usize) -> Option<(char, Style)> {
+        let &(c, dirs, style) = self.cells.get(&(x, y))?;
+        let c = c.unwrap_or(match dirs {
+            d if d == LEFT | RIGHT || d == LEFT || d == RIGHT => '─',
+            d if d == UP | DOWN || d == UP || d == DOWN => '│',
+            d if d == DOWN | RIGHT => '┌',
+            d if d == DOWN | LEFT => '┐',
+            d if d == UP | RIGHT => '└',
+            d if d == UP | LEFT => '┘',
+            d if d == UP | DOWN | RIGHT => '├',
+            d if d == UP | DOWN | LEFT => '┤',
+            d if d == DOWN | LEFT | RIGHT => '┬',
+            d if d == UP | LEFT | RIGHT => '┴',
+            _ => '┼',
+        });
+        Some((c, style))
+    }
+    // a line right from a point, then up or down to the row of another, ending with a turn right
+    pub fn path(&mut self, (x0, y0): (usize, usize), (x1, y1): (usize, usize), style: Style) {
+        for x in x0..x1 { self.line(x, y0, LEFT | RIGHT, style) }
+        if y0 == y1 { return self.line(x1, y0, LEFT | RIGHT, style) }
+        let (down, up) = if y1 > y0 { (DOWN, UP) } else { (UP, DOWN) };
+        self.line(x1, y0, LEFT | down, style);
+        for y in y0.min(y1) + 1..y0.max(y1) { self.line(x1, y, UP | DOWN, style) }
+        self.line(x1, y1, up | RIGHT, style);
+    }
+    // a row of the canvas from x on, w columns wide
+    pub fn spans(&self, x: usize, y: usize, w: usize) -> Spans<'static> {
+        let mut spans: Vec<Span> = vec![];
+        let mut at = x;
+        while at < x + w {
+            let (c, style) = self.get(at, y).unwrap_or((' ', Style::default()));
+            match spans.last_mut() {
+                Some(last) if last.style == style => last.content.to_mut().push(c),
+                _ => spans.push(Span::styled(c.to_string(), style)),
+            }
+            at += c.width().unwrap_or(1).max(1);
+        }
+        Spans::from(spans)
+    }
+}
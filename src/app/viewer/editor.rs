@@ -1,6 +1,10 @@
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use crossterm::event::{KeyCode, KeyEvent};
 use crate::data::*;
-use super::Viewer;
+use crate::app::command::Command;
+use crate::app::execute::quote;
+use crate::app::value;
+use super::{KeyResult, Viewer};
 use serde::*;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +13,24 @@ pub enum Item {
     Ev(Ev),
 }
 
+// a line of the editor, lists are indexed from their start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Type,
+    Name,
+    Id,
+    Color,
+    Quota,
+    Weight,
+    WeightType,
+    Due,
+    Time,
+    Parent,
+    Child(usize),
+    Dep(usize),
+    DepRvs(usize),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditorView {
     item: Item,
@@ -17,30 +39,48 @@ pub struct EditorView {
     deps: Vec<String>,
     deps_rvs: Vec<String>,
     tz: i64,
+    // the item was deleted, the last known state is shown
+    #[serde(default)]
+    deleted: bool,
+    // the first children are events
+    #[serde(default)]
+    chev: usize,
+    // selected line among those that can be acted on
+    #[serde(default)]
+    cursor: usize,
+    #[serde(skip)]
+    focused: bool,
+    // the value being typed into the selected line
+    #[serde(skip)]
+    input: Option<Command>,
+    // why the typed value was refused
+    #[serde(skip)]
+    error: Option<String>,
 }
 
 impl EditorView {
     pub fn new_pj(pj: Pj, db: &DataBase) -> Self {
-        let mut this = Self {
-            item: Item::Pj(pj), 
-            parent: String::new(), 
-            children: vec![], 
-            deps: vec![], tz: 0, 
-            deps_rvs: vec![], 
-        };
-        this.refresh(db);
-        return this;
+        Self::new(Item::Pj(pj), db)
     }
     pub fn new_ev(ev: Ev, db: &DataBase) -> Self {
+        Self::new(Item::Ev(ev), db)
+    }
+    fn new(item: Item, db: &DataBase) -> Self {
         let mut this = Self {
-            item: Item::Ev(ev), 
-            parent: String::new(), 
-            children: vec![], 
-            deps: vec![], tz: 0, 
-            deps_rvs: vec![], 
+            item,
+            parent: String::new(),
+            children: vec![],
+            deps: vec![], tz: 0,
+            deps_rvs: vec![],
+            deleted: false,
+            chev: 0,
+            cursor: 0,
+            focused: false,
+            input: None,
+            error: None,
         };
         this.refresh(db);
-        return this;
+        this
     }
     fn color(&self) -> tui::style::Color {
         match &self.item {
@@ -48,71 +88,157 @@ impl EditorView {
             Item::Pj(pj) => pj.color_rgb(),
         }
     }
-    fn print_one(w: usize, mut pair: (&str, &str)) -> String {
-        assert!(pair.1.width() <= w);
-        let total = pair.0.width() + pair.1.width() + 4;
-        *(&mut pair.1) = pair.1.get(
-            ..total.min(w).max(pair.0.width() + 4) - pair.0.width() - 4).unwrap();
-        let total = pair.0.width() + pair.1.width();
-        format!("{}{}{}", pair.0, " ".repeat(w.max(4+total) - total), pair.1)
-    }
     fn parse_due(&self, due: i64) -> String {
         use chrono::NaiveDateTime;
         NaiveDateTime::from_timestamp_opt(
             due + self.tz * 60 * 60, 0
         ).unwrap().format("%Y/%m/%d %H:%M").to_string()
     }
-    fn print_all(&self, w: usize, h: usize) -> Vec<String> {
+    // every line with its label and value
+    fn rows(&self) -> Vec<(Field, &'static str, String)> {
+        let list = |field: fn(usize) -> Field, label: &'static str, names: &[String]| {
+            names.iter().enumerate()
+                .map(move |(i, name)| (field(i), if i == 0 { label } else { "" }, name.clone()))
+                .collect::<Vec<_>>()
+        };
         match &self.item {
             Item::Pj(pj) => [
-                ("type",    format!("project")),
-                ("name" ,   pj.name().to_string()),
-                ("id"   ,   format!("p{}", pj.id())),
-                ("color",   format!("#{:06x}", pj.color_usize())),
-                ("done/esti quota", format!("{}/{}", pj.quota_done(), pj.quota_esti())),
-                ("weight",  format!("{}", pj.weight())),
-                ("", format!("{}", match pj.weight_type() {
+                (Field::Type,   "type",     "project".to_string()),
+                (Field::Name,   "name",     pj.name().to_string()),
+                (Field::Id,     "id",       format!("p{}", pj.id())),
+                (Field::Color,  "color",    format!("#{:06x}", pj.color_usize())),
+                (Field::Quota,  "done/esti quota", format!("{}/{}", pj.quota_done(), pj.quota_esti())),
+                (Field::Weight, "weight",   pj.weight().to_string()),
+                (Field::WeightType, "",     match pj.weight_type() {
                     WeightType::Flexible => "flexible",
                     WeightType::Reserved => "reserved",
-                })),
-                ("due", match pj.deadline() {
+                }.to_string()),
+                (Field::Due,    "due",      match pj.deadline() {
                     None => "N/A".to_string(),
                     Some(due) => self.parse_due(due),
                 }),
-                ("parent",  format!("{}", self.parent)),
+                (Field::Parent, "parent",   self.parent.clone()),
             ].into_iter()
-                .chain(self.children.iter().enumerate().map(|(i, ch)| {
-                    if i == 0 { ("children", format!("{ch}")) }
-                    else { ("", format!("{ch}")) }
-                }))
-                .chain(self.deps.iter().enumerate().map(|(i, d)| {
-                    if i == 0 { ("after", format!("d")) }
-                    else { ("", format!("{d}")) }
-                }))
-                .chain(self.deps_rvs.iter().enumerate().map(|(i, d)| {
-                    if i == 0 { ("before", format!("d")) }
-                    else { ("", format!("{d}")) }
-                }))
-                .enumerate()
-                .filter_map(|(i, (name, content))| {
-                    (i < h).then(|| Self::print_one(w, (name, &content)))
-                })
-                .collect::<Vec<_>>(),
-            Item::Ev(ev) => [
-                ("type",    format!("event")),
-                ("name",    format!("{}", ev.name())),
-                ("id",      format!("e{}", ev.id())),
-                ("esti quota", format!("{}", ev.quota_esti())),
-                ("time",    format!("{}", self.parse_due(ev.time()))),
-                ("color",   format!("#{:06x}", ev.color_usize())),
-                ("parent",  format!("{}", self.parent)),
-            ].into_iter()
-                .enumerate()
-                .filter_map(|(i, (name, content))| {
-                    (i < h).then(|| Self::print_one(w, (name, &content)))
-                })
-                .collect::<Vec<_>>()
+                .chain(list(Field::Child, "children", &self.children))
+                .chain(list(Field::Dep, "after", &self.deps))
+                .chain(list(Field::DepRvs, "before", &self.deps_rvs))
+                .collect(),
+            Item::Ev(ev) => vec![
+                (Field::Type,   "type",     "event".to_string()),
+                (Field::Name,   "name",     ev.name().to_string()),
+                (Field::Id,     "id",       format!("e{}", ev.id())),
+                (Field::Quota,  "esti quota", ev.quota_esti().to_string()),
+                (Field::Time,   "time",     self.parse_due(ev.time())),
+                (Field::Color,  "color",    format!("#{:06x}", ev.color_usize())),
+                (Field::Parent, "parent",   self.parent.clone()),
+            ],
+        }
+    }
+    // lines that can be edited or followed, events have no color to set
+    fn actionable(&self, field: Field) -> bool {
+        match field {
+            Field::Type | Field::Id => false,
+            Field::Color => matches!(self.item, Item::Pj(_)),
+            _ => true,
+        }
+    }
+    fn selected(&self) -> Option<Field> {
+        self.rows().into_iter().map(|(f, ..)| f).filter(|&f| self.actionable(f)).nth(self.cursor)
+    }
+    // text to start typing from
+    fn initial(&self, field: Field) -> String {
+        match (&self.item, field) {
+            (Item::Pj(pj), Field::Due) => pj.deadline().map(|due| self.parse_due(due)).unwrap_or_default(),
+            _ => self.rows().into_iter().find(|&(f, ..)| f == field).map(|(.., v)| v).unwrap_or_default(),
+        }
+    }
+    // the command that sets a field to the typed text
+    fn commit(&self, field: Field, text: &str) -> Result<String, String> {
+        let text = text.trim();
+        let words = text.split_whitespace().collect::<Vec<_>>();
+        let number = |s: &str| s.parse::<usize>().map_err(|_| format!("{s} is not a whole number"));
+        match field {
+            Field::Name | Field::Parent if text.is_empty() => Err(String::from("a name cannot be empty")),
+            Field::Name => Ok(format!("set name {}", quote(text))),
+            Field::Parent => Ok(format!("set parent {}", quote(text))),
+            Field::Color => {
+                let (r, g, b) = value::color(text)?;
+                Ok(format!("set color #{r:02x}{g:02x}{b:02x}"))
+            }
+            // done/esti for projects, only esti for events
+            Field::Quota => match (&self.item, text.split_once('/')) {
+                (Item::Pj(_), Some((done, esti))) => Ok(format!("set quota {} {}", number(esti.trim())?, number(done.trim())?)),
+                _ => Ok(format!("set quota {}", number(text)?)),
+            },
+            Field::Weight => Ok(format!("set weight {}", number(text)?)),
+            Field::Due if text.is_empty() || text == "-" => Ok(String::from("set due -")),
+            Field::Due | Field::Time => match words[..] {
+                [date] if field == Field::Due => { value::local(date, None)?; Ok(format!("set due {date}")) }
+                [date, clock] => {
+                    value::local(date, Some(clock))?;
+                    Ok(format!("set {} {date} {clock}", if field == Field::Due { "due" } else { "time" }))
+                }
+                _ => Err(String::from("use YYYY/MM/DD HH:MM")),
+            },
+            _ => Err(String::from("this line cannot be edited")),
+        }
+    }
+    // enter on a line: type into it, toggle it or go to the item it names
+    fn enter(&mut self, field: Field) -> KeyResult {
+        let ed = |kind: &str, name: &str| KeyResult::Command(format!("ed {kind} {}", quote(name)));
+        match (field, &self.item) {
+            (Field::WeightType, Item::Pj(pj)) => KeyResult::Command(format!("set weight {} {}", pj.weight(),
+                if pj.weight_type() == WeightType::Flexible { "reserved" } else { "flexible" })),
+            (Field::Child(i), _) if i < self.chev => ed("ev", &self.children[i]),
+            (Field::Child(i), _) => ed("pj", &self.children[i]),
+            (Field::Dep(i), _) => ed("pj", &self.deps[i]),
+            (Field::DepRvs(i), _) => ed("pj", &self.deps_rvs[i]),
+            (field, _) => {
+                let mut input = Command::new();
+                input.set(self.initial(field));
+                self.input = Some(input);
+                KeyResult::Consumed
+            }
+        }
+    }
+    // keys while typing into a line
+    fn type_key(&mut self, field: Field, key: KeyEvent) -> KeyResult {
+        let Some(input) = self.input.as_mut() else { return KeyResult::Ignored };
+        match key.code {
+            KeyCode::Char(c) => input.put(c),
+            KeyCode::Backspace => input.bks(),
+            KeyCode::Delete => input.del(),
+            KeyCode::Left => input.l(),
+            KeyCode::Right => input.r(),
+            KeyCode::Home => input.home(),
+            KeyCode::End => input.end(),
+            KeyCode::Esc => { self.input = None; self.error = None }
+            KeyCode::Enter => {
+                let text = input.get().to_string();
+                match self.commit(field, &text) {
+                    Ok(command) => {
+                        self.input = None;
+                        self.error = None;
+                        return KeyResult::Command(command)
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            _ => return KeyResult::Ignored,
+        }
+        KeyResult::Consumed
+    }
+    // a line fit into a width, label on the left and value on the right
+    fn fit(label: &str, value: &str, w: usize) -> (String, String) {
+        let room = w.saturating_sub(label.width() + 2);
+        let mut value = value.to_string();
+        if value.width() > room {
+            let mut width = 0;
+            value = value.chars().take_while(|c| { width += c.width().unwrap_or(0); width < room }).collect();
+            value.push('…');
         }
+        let pad = w.saturating_sub(label.width() + value.width()).max(1);
+        (format!("{label}{}", " ".repeat(pad)), value)
     }
 }
 
@@ -123,37 +249,62 @@ impl super::Viewer for EditorView {
     fn refresh(&mut self, db: &DataBase) {
         match &mut self.item {
             Item::Ev(ev) => {
-                *ev = db.ev_get_by_id(ev.id()).unwrap();
+                let Some(new) = db.ev_get_by_id(ev.id()) else { self.deleted = true; return };
+                *ev = new;
+                self.deleted = false;
                 self.deps.clear();
                 self.deps_rvs.clear();
                 self.children.clear();
+                self.chev = 0;
                 self.tz = db.tz() as i64;
                 self.parent = db.pj_get_by_id(ev.pp()).unwrap().name().to_string();
             },
             Item::Pj(pj) => {
-                *pj = db.pj_get_by_id(pj.id()).unwrap();
-                self.deps = pj.iter_deps()
-                    .map(|id| 
-                        db.pj_get_by_id(id).unwrap()
-                        .name().to_string())
-                    .collect::<Vec<_>>();
-                self.deps_rvs = pj.iter_deps_rvs()
-                    .map(|id|
-                        db.pj_get_by_id(id).unwrap()
-                        .name().to_string())
-                    .collect::<Vec<_>>();
-                self.children = 
-                    pj.iter_chev().map(|id| 
-                        db.ev_get_by_id(id).unwrap()
-                        .name().to_string()
-                    ).chain(
-                        pj.iter_chpj()
-                        .map(|id| db.pj_get_by_id(id).unwrap().name().to_string())
-                    ).collect::<Vec<_>>();
+                let Some(new) = db.pj_get_by_id(pj.id()) else { self.deleted = true; return };
+                *pj = new;
+                self.deleted = false;
+                let name = |id| db.pj_get_by_id(id).unwrap().name().to_string();
+                self.deps = pj.iter_deps().map(name).collect::<Vec<_>>();
+                self.deps.sort();
+                self.deps_rvs = pj.iter_deps_rvs().map(name).collect::<Vec<_>>();
+                self.deps_rvs.sort();
+                let mut chev = pj.iter_chev().map(|id| db.ev_get_by_id(id).unwrap().name().to_string()).collect::<Vec<_>>();
+                let mut chpj = pj.iter_chpj().map(name).collect::<Vec<_>>();
+                chev.sort();
+                chpj.sort();
+                self.chev = chev.len();
+                self.children = chev.into_iter().chain(chpj).collect();
                 self.tz = db.tz() as i64;
                 self.parent = db.pj_get_by_id(pj.pp()).unwrap().name().to_string();
             },
         }
+        // lists may have shrunk
+        let len = self.rows().into_iter().filter(|&(f, ..)| self.actionable(f)).count();
+        self.cursor = self.cursor.min(len.saturating_sub(1));
+    }
+    fn focus(&mut self, on: bool) -> bool {
+        self.focused = on;
+        if !on { self.input = None; self.error = None }
+        true
+    }
+    fn key(&mut self, key: KeyEvent) -> KeyResult {
+        let Some(field) = self.selected() else { return KeyResult::Ignored };
+        if self.input.is_some() { return self.type_key(field, key) }
+        let len = self.rows().into_iter().filter(|&(f, ..)| self.actionable(f)).count();
+        match key.code {
+            KeyCode::Up => self.cursor = (self.cursor + len - 1) % len,
+            KeyCode::Down => self.cursor = (self.cursor + 1) % len,
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len - 1,
+            KeyCode::Enter if !self.deleted => return self.enter(field),
+            // delete removes a dependency
+            KeyCode::Delete if !self.deleted => match field {
+                Field::Dep(i) => return KeyResult::Command(format!("rm dep {}", quote(&self.deps[i]))),
+                _ => return KeyResult::Ignored,
+            },
+            _ => return KeyResult::Ignored,
+        }
+        KeyResult::Consumed
     }
     fn render(&self, f: &mut super::Frame, rect: tui::layout::Rect) {
         // render project or event to given area
@@ -161,15 +312,59 @@ impl super::Viewer for EditorView {
         use tui::style::*;
         use tui::text::*;
         use tui::layout::*;
-        // print current item to an area of given size
-        let print = self.print_all(
-            (rect.width as usize - 2).min(64), rect.height as usize - 2);
-        let print = print.into_iter().map(|x| Spans::from(Span::raw(x)));
-        let print = Text::from(print.collect::<Vec<_>>());
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let lit = Style::default().add_modifier(Modifier::REVERSED);
+        let err = Style::default().fg(Color::Rgb(255, 90, 90));
+        let w = (rect.width as usize).saturating_sub(2).min(64);
+        let h = (rect.height as usize).saturating_sub(2);
+        let selected = self.focused.then(|| self.selected()).flatten();
+        let mut lines = vec![];
+        let mut at = 0;
+        for (field, label, value) in self.rows() {
+            if Some(field) == selected { at = lines.len() }
+            let spans = match (&self.input, Some(field) == selected) {
+                // the typed text with the character under the cursor lit
+                (Some(input), true) => {
+                    let text = input.get();
+                    let (label, _) = Self::fit(label, &format!("{text} "), w);
+                    let mut width = 0;
+                    let at = text.char_indices().find(|&(_, c)| {
+                        width += c.width().unwrap_or(0);
+                        width > input.xcursor() as usize
+                    }).map_or(text.len(), |(i, _)| i);
+                    let end = text[at..].chars().next().map_or(at, |c| at + c.len_utf8());
+                    vec![
+                        Span::styled(label, faint),
+                        Span::raw(text[..at].to_string()),
+                        Span::styled(if at == end { " ".to_string() } else { text[at..end].to_string() }, lit),
+                        Span::raw(text[end..].to_string()),
+                    ]
+                }
+                (_, is) => {
+                    let (label, value) = Self::fit(label, &value, w);
+                    vec![Span::styled(label, faint), Span::styled(value, if is { lit } else { Style::default() })]
+                }
+            };
+            lines.push(Spans::from(spans));
+        }
+        if let Some(e) = &self.error {
+            lines.insert(at + 1, Spans::from(Span::styled(e.clone(), err)));
+        }
+        // the selected line stays in sight
+        let skip = (at + 2).saturating_sub(h);
+        let lines = lines.into_iter().skip(skip).take(h).collect::<Vec<_>>();
+        let title = match (self.deleted, self.focused) {
+            (true, _) => "deleted",
+            (false, true) => "esc to leave",
+            (false, false) => "",
+        };
+        let mut border = Style { fg: Some(self.color()), ..Default::default() };
+        if self.focused { border = border.add_modifier(Modifier::BOLD) }
         let block = Block::default()
             .borders(Borders::ALL)
-            .border_style(Style { fg: Some(self.color()), ..Default::default() });
+            .title(title)
+            .border_style(border);
         f.render_widget(
-            Paragraph::new(print).alignment(Alignment::Center).block(block), rect);
+            Paragraph::new(Text::from(lines)).alignment(Alignment::Center).block(block), rect);
     }
-}
\ No newline at end of file
+}
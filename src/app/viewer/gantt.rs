@@ -0,0 +1,327 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use unicode_width::UnicodeWidthStr;
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::style::{Color, Modifier, Style};
+use crate::app::execute::quote;
+use crate::config::WeekStart;
+use crate::data::*;
+use super::canvas::*;
+use super::{KeyResult, Viewer};
+use serde::*;
+
+// columns shown before today when the chart is not panned
+const MARGIN: i64 = 4;
+// columns panned by left and right
+const PAN: i64 = 4;
+// widest column of names
+const NAME_W: usize = 24;
+// how far ahead the auto planner looks at most, it stops once every hour is placed
+const HORIZON: i64 = 366 * 24 * 60 * 60;
+
+// time a column of the chart stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Zoom {
+    // a quarter of a day, labeled by day
+    Day,
+    // a day, labeled by week
+    Week,
+    // a week, labeled by month
+    Month,
+}
+
+impl std::str::FromStr for Zoom {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(Zoom::Day),
+            "week" => Ok(Zoom::Week),
+            "month" => Ok(Zoom::Month),
+            _ => Err(format!("invalid zoom {s}, use day, week or month")),
+        }
+    }
+}
+
+impl std::fmt::Display for Zoom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Zoom::Day => "day",
+            Zoom::Week => "week",
+            Zoom::Month => "month",
+        })
+    }
+}
+
+impl Zoom {
+    fn seconds(self) -> i64 {
+        match self {
+            Zoom::Day => 6 * 60 * 60,
+            Zoom::Week => 24 * 60 * 60,
+            Zoom::Month => 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+// a project of the chart
+#[derive(Debug, Clone)]
+struct Row {
+    name: String,
+    depth: usize,
+    color: Color,
+    // hours left
+    left: usize,
+    // utc start and end of the work left, by events and proposals or right after what it waits for
+    work: Option<(i64, i64)>,
+    deadline: Option<i64>,
+    // rows it waits for
+    deps: Vec<usize>,
+}
+
+impl Row {
+    fn late(&self) -> bool {
+        matches!((self.work, self.deadline), (Some((_, end)), Some(d)) if end > d)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GanttView {
+    root: usize,
+    zoom: Zoom,
+    // columns panned from where today is shown
+    #[serde(default)]
+    shift: i64,
+    #[serde(skip)]
+    name: String,
+    #[serde(skip)]
+    rows: Vec<Row>,
+    #[serde(skip)]
+    cursor: usize,
+    // set by the app from the config
+    #[serde(skip)]
+    week_start: WeekStart,
+    #[serde(skip)]
+    hours: (u32, u32),
+    #[serde(skip)]
+    tz: i64,
+    #[serde(skip)]
+    now: i64,
+    #[serde(skip)]
+    focused: bool,
+}
+
+impl GanttView {
+    pub fn new(root: usize, zoom: Zoom) -> Self {
+        GanttView {
+            root, zoom, shift: 0, name: String::new(), rows: vec![], cursor: 0, week_start: WeekStart::default(),
+            hours: (9, 18), tz: 0, now: 0, focused: false,
+        }
+    }
+    pub fn set(&mut self, root: usize, zoom: Zoom) {
+        if root != self.root { self.cursor = 0 }
+        self.root = root;
+        self.set_zoom(zoom);
+    }
+    fn set_zoom(&mut self, zoom: Zoom) {
+        self.zoom = zoom;
+        self.shift = 0;
+    }
+    // projects below a project, depth first
+    fn walk(db: &DataBase, id: usize, depth: usize, ids: &mut Vec<(usize, usize)>) {
+        let Some(pj) = db.pj_get_by_id(id) else { return };
+        let mut chpj = pj.iter_chpj().filter_map(|c| db.pj_get_by_id(c)).collect::<Vec<_>>();
+        chpj.sort_by(|a, b| a.name().cmp(b.name()));
+        // the root of everything has nothing to plan
+        let below = if id == 0 { depth } else { ids.push((id, depth)); depth + 1 };
+        for ch in chpj {
+            Self::walk(db, ch.id(), below, ids);
+        }
+    }
+    // the start of the column today is in, in local seconds
+    fn anchor(&self) -> i64 {
+        let today = NaiveDateTime::from_timestamp_opt(self.now + self.tz, 0).unwrap().date();
+        let back = match self.zoom {
+            Zoom::Month => (today.weekday().num_days_from_monday() + 7 - self.week_start.weekday().num_days_from_monday()) % 7,
+            _ => 0,
+        };
+        (today - Duration::days(back as i64)).and_hms_opt(0, 0, 0).unwrap().timestamp()
+    }
+    // the column of a utc time, today's column is 0
+    fn col(&self, t: i64) -> i64 {
+        (t + self.tz - self.anchor()).div_euclid(self.zoom.seconds())
+    }
+    // the local date a column starts on
+    fn date(&self, col: i64) -> NaiveDate {
+        NaiveDateTime::from_timestamp_opt(self.anchor() + col * self.zoom.seconds(), 0).unwrap().date()
+    }
+    // label of the columns starting a day, a week or a month
+    fn label(&self, col: i64) -> Option<String> {
+        let date = self.date(col);
+        match self.zoom {
+            Zoom::Day if col.rem_euclid(4) == 0 => Some(date.format("%d").to_string()),
+            Zoom::Week if date.weekday() == self.week_start.weekday() => Some(date.format("%m/%d").to_string()),
+            Zoom::Month if date.day() <= 7 => Some(date.format("%b").to_string()),
+            _ => None,
+        }
+    }
+    // the header and a line for each row below it
+    fn draw(&self, width: usize) -> Canvas {
+        let mut canvas = Canvas::default();
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let red = Style::default().fg(Color::Rgb(255, 90, 90));
+        let green = Style::default().fg(Color::Rgb(80, 200, 120));
+        let first = self.shift - MARGIN;
+        // the canvas starts a column left of the window, work off the window is drawn to its edges
+        // so arrows still point the right way
+        let x = |col: i64| (first..first + width as i64).contains(&col).then(|| (col - first + 1) as usize);
+        let clamp = |col: i64| (col.clamp(first - 1, first + width as i64) - first + 1) as usize;
+        for col in first..first + width as i64 {
+            if let Some(label) = self.label(col) { canvas.text(x(col).unwrap(), 0, &label, faint) }
+        }
+        for (i, row) in self.rows.iter().enumerate() {
+            let y = i + 1;
+            let style = Style::default().fg(row.color);
+            let cd = row.deadline.map(|d| self.col(d));
+            let mut end = cd.map(|d| d + 1);
+            if let Some((s, e)) = row.work {
+                let (cs, ce) = (self.col(s), self.col(e - 1) + 1);
+                for col in cs..ce {
+                    let late = cd.is_some_and(|d| col > d);
+                    if let Some(x) = x(col) { canvas.text(x, y, "█", if late { red } else { style }) }
+                }
+                // slack before the deadline is hatched
+                for col in ce..cd.unwrap_or(ce) {
+                    if let Some(x) = x(col) { canvas.text(x, y, "░", style) }
+                }
+                end = Some(end.unwrap_or(ce).max(ce));
+            }
+            if let Some(x) = cd.and_then(x) {
+                canvas.text(x, y, "◆", if row.late() { red } else if row.work.is_none() { green } else { style })
+            }
+            let label = match row.work {
+                Some(_) => format!(" {}h", row.left),
+                None => String::from(" done"),
+            };
+            if let Some(x) = end.and_then(x) { canvas.text(x, y, &label, faint) }
+        }
+        // arrows run from the end of what a project waits for, and turn into its start from the column before
+        // they are red when the project starts before the end, and go under the bars they cross
+        for (i, row) in self.rows.iter().enumerate() {
+            let Some((s, _)) = row.work else { continue };
+            for &d in row.deps.iter() {
+                let Some((_, e)) = self.rows[d].work else { continue };
+                let style = if s < e { red } else { faint };
+                let turn = clamp(self.col(s)).saturating_sub(1);
+                canvas.path((clamp(self.col(e - 1) + 1).min(turn), d + 1), (turn, i + 1), style);
+            }
+        }
+        // today runs down the chart behind the bars
+        if let Some(today) = x(self.col(self.now)) {
+            let (c, _) = canvas.get(today, 0).unwrap_or((' ', faint));
+            canvas.text(today, 0, &c.to_string(), red.add_modifier(Modifier::REVERSED));
+            for y in 1..=self.rows.len() { canvas.line(today, y, UP | DOWN, red) }
+        }
+        canvas
+    }
+}
+
+impl Viewer for GanttView {
+    fn name(&self) -> String {
+        "gantt".to_string()
+    }
+    fn set_config(&mut self, config: &crate::config::Config) {
+        self.week_start = config.week_start;
+        self.hours = config.work_hours;
+    }
+    fn refresh(&mut self, db: &crate::DataBase) {
+        // a deleted root falls back to the root of everything
+        if db.pj_get_by_id(self.root).is_none() { self.root = 0 }
+        self.name = db.pj_get_by_id(self.root).map(|pj| pj.name().to_string()).unwrap_or_default();
+        self.tz = db.tz() as i64 * 60 * 60;
+        self.now = chrono::Utc::now().timestamp();
+        let now = self.now;
+        let mut ids = vec![];
+        Self::walk(db, self.root, 0, &mut ids);
+        let pjs = ids.iter().filter_map(|&(id, _)| db.pj_get_by_id(id)).collect::<Vec<_>>();
+        // the planned work is the events to come and what the auto planner proposes
+        let mut planned = db.auto_schedule(now, now + HORIZON, self.hours, &[]).into_iter()
+            .map(|p| (p.pj, p.time, p.time + p.quota as i64 * QUOTA_UNIT)).collect::<Vec<_>>();
+        planned.extend(db.ev_list().unwrap_or_default().iter().filter_map(|n| db.ev_get_by_name(n))
+            .filter(|ev| ev.time() >= now)
+            .map(|ev| (ev.pp(), ev.time(), ev.time() + ev.quota_esti() as i64 * QUOTA_UNIT)));
+        let gray = Color::Rgb(127, 127, 127);
+        self.rows = pjs.iter().zip(ids.iter()).map(|(pj, &(_, depth))| {
+            let left = pj.quota_esti().saturating_sub(pj.quota_done());
+            let work = planned.iter().filter(|&&(id, ..)| id == pj.id())
+                .fold(None, |w: Option<(i64, i64)>, &(_, s, e)| Some(w.map_or((s, e), |(ws, we)| (ws.min(s), we.max(e)))));
+            Row {
+                name: pj.name().to_string(), depth,
+                color: match pj.color_usize() { 0 => gray, c => Color::Rgb((c >> 16) as u8, (c >> 8) as u8, c as u8) },
+                left, work: if left > 0 { work } else { None }, deadline: pj.deadline(),
+                deps: pj.iter_deps().filter_map(|d| pjs.iter().position(|p| p.id() == d)).collect(),
+            }
+        }).collect();
+        // work the planner found no hours for starts as soon as what it waits for ends,
+        // and takes a working day for each day's worth of working hours
+        let per_day = (self.hours.1.saturating_sub(self.hours.0) as usize).max(1);
+        let unplanned = (0..self.rows.len()).filter(|&i| self.rows[i].left > 0 && self.rows[i].work.is_none()).collect::<Vec<_>>();
+        for _ in 0..self.rows.len() {
+            for &i in unplanned.iter() {
+                let start = self.rows[i].deps.iter().filter_map(|&d| self.rows[d].work).map(|(_, e)| e).fold(now, i64::max);
+                let days = self.rows[i].left.div_ceil(per_day) as i64;
+                self.rows[i].work = Some((start, start + days * 24 * 60 * 60));
+            }
+        }
+        self.cursor = self.cursor.min(self.rows.len().saturating_sub(1));
+    }
+    fn focus(&mut self, on: bool) -> bool {
+        self.focused = on;
+        true
+    }
+    fn key(&mut self, key: KeyEvent) -> KeyResult {
+        let len = self.rows.len();
+        match key.code {
+            KeyCode::Up if len > 0 => self.cursor = (self.cursor + len - 1) % len,
+            KeyCode::Down if len > 0 => self.cursor = (self.cursor + 1) % len,
+            KeyCode::Left => self.shift -= PAN,
+            KeyCode::Right => self.shift += PAN,
+            KeyCode::Home => self.shift = 0,
+            KeyCode::Char('+') | KeyCode::Char('=') => self.set_zoom(match self.zoom { Zoom::Month => Zoom::Week, _ => Zoom::Day }),
+            KeyCode::Char('-') => self.set_zoom(match self.zoom { Zoom::Day => Zoom::Week, _ => Zoom::Month }),
+            KeyCode::Enter if len > 0 => return KeyResult::Command(format!("ed pj {}", quote(&self.rows[self.cursor].name))),
+            _ => return KeyResult::Ignored,
+        }
+        KeyResult::Consumed
+    }
+    fn render(&self, f: &mut super::Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        use tui::text::*;
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let red = Style::default().fg(Color::Rgb(255, 90, 90));
+        let w = (rect.width as usize).saturating_sub(2);
+        let h = (rect.height as usize).saturating_sub(3);
+        let name_w = self.rows.iter().map(|r| r.depth * 2 + r.name.width()).max().unwrap_or(0).min(NAME_W).min(w / 3);
+        let chart_w = w.saturating_sub(name_w + 1);
+        let canvas = self.draw(chart_w);
+        let skip = (self.cursor + 1).saturating_sub(h);
+        let mut header = vec![Span::raw(" ".repeat(name_w + 1))];
+        header.extend(canvas.spans(1, 0, chart_w).0);
+        let mut lines = vec![Spans::from(header)];
+        lines.extend(self.rows.iter().enumerate().skip(skip).take(h).map(|(i, row)| {
+            let mut name = format!("{}{}", "  ".repeat(row.depth), row.name);
+            while name.width() > name_w { name.pop(); }
+            let pad = name_w - name.width();
+            let mut style = if row.late() { red } else if row.work.is_none() { faint } else { Style::default() };
+            if self.focused && i == self.cursor { style = style.add_modifier(Modifier::REVERSED) }
+            let mut spans = vec![Span::styled(name, style), Span::raw(" ".repeat(pad + 1))];
+            spans.extend(canvas.spans(1, i + 1, chart_w).0);
+            Spans::from(spans)
+        }));
+        let late = self.rows.iter().filter(|r| r.late()).count();
+        let mut title = format!("gantt of {} by {}", self.name, self.zoom);
+        if late > 0 { title.push_str(&format!(", {late} late")) }
+        if self.focused { title.push_str(", +/- zoom, arrows pan, enter edits, esc to leave") }
+        let mut border = Style::default();
+        if self.focused { border = border.add_modifier(Modifier::BOLD) }
+        let block = Block::default().borders(Borders::ALL).title(title).border_style(border);
+        f.render_widget(Paragraph::new(lines).block(block), rect);
+    }
+}
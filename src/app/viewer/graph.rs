@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use unicode_width::UnicodeWidthStr;
+use super::canvas::*;
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::style::{Color, Modifier, Style};
+use super::{KeyResult, Viewer};
+use serde::*;
+
+// empty columns between layers, where edges turn
+const GAP: usize = 6;
+// lines taken by a box and the space below it
+const NODE_H: usize = 4;
+
+// a project of the graph
+#[derive(Debug, Clone)]
+struct Node {
+    name: String,
+    // hours left
+    left: usize,
+    // peers it waits for, by index
+    deps: Vec<usize>,
+    layer: usize,
+    // a dependency still has work left
+    blocked: bool,
+    in_cycle: bool,
+    critical: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphView {
+    // the projects shown are its children
+    parent: usize,
+    #[serde(skip)]
+    name: String,
+    #[serde(skip)]
+    nodes: Vec<Node>,
+    // panned by the keyboard
+    #[serde(skip)]
+    offset: (usize, usize),
+    #[serde(skip)]
+    focused: bool,
+}
+
+impl GraphView {
+    pub fn new(parent: usize) -> Self {
+        GraphView { parent, name: String::new(), nodes: vec![], offset: (0, 0), focused: false }
+    }
+    pub fn set_parent(&mut self, parent: usize) {
+        self.parent = parent;
+        self.offset = (0, 0);
+    }
+    // longest chains of dependencies decide the layers
+    // projects on a cycle share the layer after what they wait for, and what waits for them comes after
+    fn layer(nodes: &mut [Node]) {
+        let mut done = vec![false; nodes.len()];
+        let mut layer = 0;
+        while done.iter().any(|d| !d) {
+            let ready = (0..nodes.len())
+                .filter(|&i| !done[i] && nodes[i].deps.iter().all(|&d| done[d]))
+                .collect::<Vec<_>>();
+            // with nothing ready, what is left waits on a cycle
+            let in_cycle = ready.is_empty();
+            let ready = match in_cycle {
+                true => (0..nodes.len()).filter(|&i| !done[i] && Self::reaches(nodes, i, i, &done)).collect(),
+                false => ready,
+            };
+            for &i in ready.iter() {
+                nodes[i].layer = layer;
+                nodes[i].in_cycle = in_cycle;
+                done[i] = true;
+            }
+            layer += 1;
+        }
+    }
+    // whether x waits for y through projects not placed yet
+    fn reaches(nodes: &[Node], x: usize, y: usize, done: &[bool]) -> bool {
+        let mut seen = vec![false; nodes.len()];
+        let mut stack = nodes[x].deps.clone();
+        while let Some(d) = stack.pop() {
+            if d == y { return true }
+            if done[d] || seen[d] { continue }
+            seen[d] = true;
+            stack.extend(nodes[d].deps.iter().copied());
+        }
+        false
+    }
+    // the chain with the most hours left, from its first project to its last
+    fn critical(nodes: &mut [Node]) {
+        let mut order = (0..nodes.len()).filter(|&i| !nodes[i].in_cycle).collect::<Vec<_>>();
+        order.sort_by_key(|&i| nodes[i].layer);
+        let mut total = vec![0; nodes.len()];
+        let mut prev = vec![None; nodes.len()];
+        for &i in order.iter() {
+            let best = nodes[i].deps.iter().copied().max_by_key(|&d| (total[d], std::cmp::Reverse(d)));
+            total[i] = nodes[i].left + best.map_or(0, |d| total[d]);
+            prev[i] = best;
+        }
+        let Some(mut at) = order.iter().copied().max_by_key(|&i| (total[i], std::cmp::Reverse(i))) else { return };
+        if total[at] == 0 { return }
+        loop {
+            nodes[at].critical = true;
+            match prev[at] {
+                Some(p) => at = p,
+                None => break,
+            }
+        }
+    }
+    // top left corner of every box, and the width of each layer
+    fn place(&self) -> (Vec<(usize, usize)>, Vec<usize>) {
+        let layers = self.nodes.iter().map(|n| n.layer + 1).max().unwrap_or(0);
+        let mut width = vec![0; layers];
+        for n in self.nodes.iter() {
+            width[n.layer] = width[n.layer].max(Self::label(n).width() + 4);
+        }
+        let x = width.iter().scan(0, |x, w| { let at = *x; *x += w + GAP; Some(at) }).collect::<Vec<_>>();
+        let mut rows = vec![0; layers];
+        let mut at = vec![(0, 0); self.nodes.len()];
+        // projects go down their layer in the order of the first of their dependencies
+        let mut order = (0..self.nodes.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| (self.nodes[i].layer, self.nodes[i].deps.iter().min().copied(), i));
+        for i in order {
+            let l = self.nodes[i].layer;
+            at[i] = (x[l], rows[l] * NODE_H);
+            rows[l] += 1;
+        }
+        (at, width)
+    }
+    fn label(node: &Node) -> String {
+        let mark = if node.in_cycle { "⟳ " } else { "" };
+        format!("{mark}{} {}h", node.name, node.left)
+    }
+    fn draw(&self) -> Canvas {
+        let mut canvas = Canvas::default();
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let hot = Style::default().fg(Color::Rgb(255, 200, 60)).add_modifier(Modifier::BOLD);
+        let (at, width) = self.place();
+        // edges run right from a dependency, turn in the gap before the project and go into its left side
+        // edges over more than one layer go below the boxes in between
+        for (i, node) in self.nodes.iter().enumerate() {
+            for &d in node.deps.iter() {
+                let dep = &self.nodes[d];
+                if dep.layer >= node.layer { continue }
+                let style = if dep.critical && node.critical { hot } else { faint };
+                let (xs, ys) = (at[d].0 + width[dep.layer], at[d].1 + 1);
+                let (xt, yt) = (at[i].0 - 1, at[i].1 + 1);
+                let xg = xt - GAP / 2;
+                let mut from = (xs, ys);
+                if node.layer > dep.layer + 1 {
+                    // the free row under the project, left of where the short edges turn
+                    let lane = at[i].1 + NODE_H - 1;
+                    canvas.path(from, (xs + 1, lane), style);
+                    from = (xs + 2, lane);
+                }
+                canvas.path(from, (xg, yt), style);
+                for x in xg + 1..xt { canvas.line(x, yt, LEFT | RIGHT, style) }
+                canvas.text(xt, yt, "▶", style);
+            }
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            let (x, y) = at[i];
+            let w = width[node.layer];
+            let border = match node {
+                n if n.in_cycle => Style::default().fg(Color::Rgb(255, 90, 90)),
+                n if n.critical => hot,
+                n if n.left == 0 => Style::default().fg(Color::Rgb(80, 200, 120)),
+                _ => Style::default(),
+            };
+            // blocked projects cannot start yet
+            let name = if node.blocked { faint } else { Style::default() };
+            canvas.text(x, y, &format!("┌{}┐", "─".repeat(w - 2)), border);
+            canvas.text(x, y + 1, "│", border);
+            canvas.text(x + 1, y + 1, &format!(" {:<width$}", Self::label(node), width = w - 3), name);
+            canvas.text(x + w - 1, y + 1, "│", border);
+            canvas.text(x, y + 2, &format!("└{}┘", "─".repeat(w - 2)), border);
+        }
+        canvas
+    }
+}
+
+impl Viewer for GraphView {
+    fn name(&self) -> String {
+        "graph".to_string()
+    }
+    fn refresh(&mut self, db: &crate::DataBase) {
+        // a deleted parent falls back to the root
+        let Some(pp) = db.pj_get_by_id(self.parent).or_else(|| db.pj_get_by_id(0)) else { return };
+        self.parent = pp.id();
+        self.name = pp.name().to_string();
+        let mut peers = pp.iter_chpj().filter_map(|c| db.pj_get_by_id(c)).collect::<Vec<_>>();
+        peers.sort_by(|a, b| a.name().cmp(b.name()));
+        let index = peers.iter().enumerate().map(|(i, pj)| (pj.id(), i)).collect::<HashMap<_, _>>();
+        self.nodes = peers.iter().map(|pj| {
+            let mut deps = pj.iter_deps().filter_map(|d| index.get(&d).copied()).collect::<Vec<_>>();
+            deps.sort();
+            Node {
+                name: pj.name().to_string(), left: pj.quota_esti().saturating_sub(pj.quota_done()),
+                deps, layer: 0, blocked: false, in_cycle: false, critical: false,
+            }
+        }).collect();
+        for i in 0..self.nodes.len() {
+            self.nodes[i].blocked = self.nodes[i].deps.iter().any(|&d| self.nodes[d].left > 0);
+        }
+        Self::layer(&mut self.nodes);
+        Self::critical(&mut self.nodes);
+    }
+    fn focus(&mut self, on: bool) -> bool {
+        self.focused = on;
+        true
+    }
+    fn key(&mut self, key: KeyEvent) -> KeyResult {
+        let (x, y) = &mut self.offset;
+        match key.code {
+            KeyCode::Left => *x = x.saturating_sub(4),
+            KeyCode::Right => *x += 4,
+            KeyCode::Up => *y = y.saturating_sub(NODE_H),
+            KeyCode::Down => *y += NODE_H,
+            KeyCode::Home => self.offset = (0, 0),
+            _ => return KeyResult::Ignored,
+        }
+        KeyResult::Consumed
+    }
+    fn render(&self, f: &mut super::Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        let canvas = self.draw();
+        let (w, h) = ((rect.width as usize).saturating_sub(2), (rect.height as usize).saturating_sub(2));
+        let lines = (0..h).map(|row| canvas.spans(self.offset.0, row + self.offset.1, w)).collect::<Vec<_>>();
+        let critical = self.nodes.iter().filter(|n| n.critical).map(|n| n.left).sum::<usize>();
+        let cycles = self.nodes.iter().filter(|n| n.in_cycle).count();
+        let mut title = format!("dependencies in {}, critical path {critical}h", self.name);
+        if cycles > 0 { title.push_str(&format!(", {cycles} in cycles")) }
+        if self.focused { title.push_str(", arrows to pan, esc to leave") }
+        let mut border = Style::default();
+        if self.focused { border = border.add_modifier(Modifier::BOLD) }
+        let block = Block::default().borders(Borders::ALL).title(title).border_style(border);
+        f.render_widget(Paragraph::new(lines).block(block), rect);
+    }
+}
@@ -0,0 +1,202 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use tui::style::{Color, Style};
+use crate::config::WeekStart;
+use crate::data::*;
+use super::Viewer;
+use serde::*;
+
+// weeks of a year, the current one included
+const WEEKS: usize = 53;
+// width of the weekday labels
+const LABEL_W: usize = 4;
+// shades from no work to the busiest days
+const LEVELS: usize = 4;
+
+// what makes up the hours of a day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Count {
+    // quota of the events that took place on the day
+    Events,
+    // quota marked done on the day, read back from the log
+    Done,
+}
+
+impl std::str::FromStr for Count {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "events" => Ok(Count::Events),
+            "done" => Ok(Count::Done),
+            _ => Err(format!("invalid count {s}, use events or done")),
+        }
+    }
+}
+
+impl std::fmt::Display for Count {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Count::Events => "events",
+            Count::Done => "done",
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeatView {
+    root: usize,
+    count: Count,
+    #[serde(skip)]
+    name: String,
+    #[serde(skip)]
+    color: Option<Color>,
+    // hours of each day from the first shown to today
+    #[serde(skip)]
+    hours: Vec<usize>,
+    #[serde(skip)]
+    first: Option<NaiveDate>,
+    // set by the app from the config
+    #[serde(skip)]
+    week_start: WeekStart,
+}
+
+impl HeatView {
+    pub fn new(root: usize, count: Count) -> Self {
+        HeatView { root, count, name: String::new(), color: None, hours: vec![], first: None, week_start: WeekStart::default() }
+    }
+    pub fn set(&mut self, root: usize, count: Count) {
+        self.root = root;
+        self.count = count;
+    }
+    // the project and the projects below it
+    fn walk(db: &DataBase, id: usize, ids: &mut Vec<usize>) {
+        let Some(pj) = db.pj_get_by_id(id) else { return };
+        ids.push(id);
+        for ch in pj.iter_chpj() {
+            Self::walk(db, ch, ids);
+        }
+    }
+    // days in a row with work up to today, or up to yesterday while today has none yet, and the longest run
+    fn streaks(&self) -> (usize, usize) {
+        let mut runs = vec![0];
+        for &h in self.hours.iter() {
+            match h {
+                0 => runs.push(0),
+                _ => *runs.last_mut().unwrap() += 1,
+            }
+        }
+        let longest = runs.iter().copied().max().unwrap_or(0);
+        let current = match self.hours.last() {
+            Some(0) => runs.get(runs.len().wrapping_sub(2)).copied().unwrap_or(0),
+            _ => runs.last().copied().unwrap_or(0),
+        };
+        (current, longest)
+    }
+    // the project color faded into the background by how busy a day is
+    fn shade(&self, hours: usize, max: usize) -> Color {
+        let (r, g, b) = match self.color {
+            Some(Color::Rgb(r, g, b)) => (r, g, b),
+            _ => (64, 196, 99),
+        };
+        let level = match hours {
+            0 => return Color::Rgb(50, 50, 50),
+            h => (h * LEVELS).div_ceil(max.max(1)).min(LEVELS),
+        };
+        let mix = |c: u8| (50.0 + (c as f64 - 50.0) * level as f64 / LEVELS as f64) as u8;
+        Color::Rgb(mix(r), mix(g), mix(b))
+    }
+}
+
+impl Viewer for HeatView {
+    fn name(&self) -> String {
+        "heatmap".to_string()
+    }
+    fn set_config(&mut self, config: &crate::config::Config) {
+        self.week_start = config.week_start;
+    }
+    fn refresh(&mut self, db: &crate::DataBase) {
+        // a deleted root falls back to the root of everything
+        if db.pj_get_by_id(self.root).is_none() { self.root = 0 }
+        let Some(root) = db.pj_get_by_id(self.root) else { return };
+        self.name = root.name().to_string();
+        self.color = match root.color_usize() {
+            0 => None,
+            c => Some(Color::Rgb((c >> 16) as u8, (c >> 8) as u8, c as u8)),
+        };
+        let tz = db.tz() as i64 * 60 * 60;
+        let local = |t: i64| NaiveDateTime::from_timestamp_opt(t + tz, 0).unwrap().date();
+        let today = local(chrono::Utc::now().timestamp());
+        // the columns are whole weeks, the last one holds today
+        let back = (today.weekday().num_days_from_monday() + 7 - self.week_start.weekday().num_days_from_monday()) % 7;
+        let first = today - Duration::days(back as i64 + (WEEKS as i64 - 1) * 7);
+        self.first = Some(first);
+        self.hours = vec![0; (today - first).num_days() as usize + 1];
+        let mut ids = vec![];
+        Self::walk(db, self.root, &mut ids);
+        let mut add = |t: i64, h: usize| {
+            let day = (local(t) - first).num_days();
+            if let Some(hours) = usize::try_from(day).ok().and_then(|d| self.hours.get_mut(d)) { *hours += h }
+        };
+        match self.count {
+            Count::Events => {
+                let evs = ids.iter().filter_map(|&id| db.pj_get_by_id(id)).flat_map(|pj| pj.iter_chev().collect::<Vec<_>>());
+                let now = chrono::Utc::now().timestamp();
+                for ev in evs.filter_map(|id| db.ev_get_by_id(id)).filter(|ev| ev.time() <= now) {
+                    add(ev.time(), ev.quota_esti());
+                }
+            }
+            // hours taken back are not counted against the day
+            Count::Done => for &id in ids.iter() {
+                let log = db.pj_quota_log(id);
+                for w in log.windows(2).filter(|w| w[1].time > 0) {
+                    add(w[1].time, w[1].done.saturating_sub(w[0].done));
+                }
+            },
+        }
+    }
+    fn render(&self, f: &mut super::Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        use tui::text::*;
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let what = match self.count { Count::Events => "hours of events", Count::Done => "hours done" };
+        let block = Block::default().borders(Borders::ALL).title(format!("{what} in {}", self.name));
+        let Some(first) = self.first else { return f.render_widget(block, rect) };
+        // the latest weeks that fit, two columns each
+        let weeks = ((rect.width as usize).saturating_sub(2 + LABEL_W) / 2).min(WEEKS);
+        let skip = WEEKS - weeks;
+        let max = self.hours.iter().copied().max().unwrap_or(0);
+        // a month is named over the week its first day is in
+        let mut months = " ".repeat(LABEL_W + weeks * 2).chars().collect::<Vec<_>>();
+        for c in 0..weeks {
+            let last = first + Duration::days(((skip + c) * 7 + 6) as i64);
+            let x = LABEL_W + c * 2;
+            if last.day() <= 7 && months[x.saturating_sub(1)] == ' ' {
+                for (i, ch) in last.format("%b").to_string().chars().enumerate() {
+                    if let Some(m) = months.get_mut(x + i) { *m = ch }
+                }
+            }
+        }
+        let mut lines = vec![Spans::from(Span::styled(months.into_iter().collect::<String>(), faint))];
+        for row in 0..7 {
+            let day = first + Duration::days(row as i64);
+            let label = if row % 2 == 1 { day.format("%a").to_string() } else { String::new() };
+            let mut spans = vec![Span::styled(format!("{label:<LABEL_W$}"), faint)];
+            for c in 0..weeks {
+                let i = (skip + c) * 7 + row;
+                // days after today are left out of the last week
+                let Some(&h) = self.hours.get(i) else { break };
+                spans.push(Span::styled("■ ", Style::default().fg(self.shade(h, max))));
+            }
+            lines.push(Spans::from(spans));
+        }
+        let (current, longest) = self.streaks();
+        let total = self.hours.iter().sum::<usize>();
+        let mut footer = format!("{total}h in a year, streak {current} days, longest {longest} days");
+        if let Some(busiest) = self.hours.iter().position(|&h| h == max && h > 0) {
+            let date = first + Duration::days(busiest as i64);
+            footer.push_str(&format!(", busiest {} with {max}h", date.format("%Y/%m/%d")));
+        }
+        lines.push(Spans::default());
+        lines.push(Spans::from(Span::styled(footer, faint)));
+        f.render_widget(Paragraph::new(lines).block(block), rect);
+    }
+}
@@ -0,0 +1,126 @@
+use super::*;
+use crate::app::execute::CommandInfo;
+
+// one command as shown by help
+#[derive(Debug, Clone)]
+pub struct HelpEntry {
+    // internal or the name of a plugin
+    origin: String,
+    name: String,
+    summary: String,
+    syntax: String,
+    // syntax, description and regex of each argument
+    args: Vec<(String, String, String)>,
+    examples: Vec<String>,
+}
+
+impl HelpEntry {
+    pub fn new(info: &CommandInfo, origin: &str, examples: Vec<String>) -> Self {
+        let args = (0..info.pattern.len())
+            .filter(|&i| !matches!(info.pattern[i], crate::app::execute::ArgPattern::Word(_)))
+            .map(|i| {
+                let about = match info.default_of(i) {
+                    Some(d) => format!("{} (default {d})", info.about[i].1),
+                    None => info.about[i].1.to_string(),
+                };
+                (info.syntax_of(i), about, info.regex_of(i).to_string())
+            })
+            .collect();
+        HelpEntry {
+            origin: origin.to_string(),
+            name: info.name.to_string(),
+            summary: info.summary.to_string(),
+            syntax: info.syntax(),
+            args,
+            examples,
+        }
+    }
+    // case insensitive search in name, summary and syntax
+    fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.name, &self.summary, &self.syntax].iter().any(|s| s.to_lowercase().contains(&query))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HelpView {
+    query: String,
+    // rebuilt by the app on every refresh
+    #[serde(skip)]
+    entries: Vec<HelpEntry>,
+}
+
+impl HelpView {
+    // entries are filled by the app, they depend on the plugins of the page
+    pub fn new(query: impl Into<String>) -> Self {
+        HelpView { query: query.into(), entries: vec![] }
+    }
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.query = query.into();
+    }
+    pub fn set_entries(&mut self, entries: Vec<HelpEntry>) {
+        self.entries = entries;
+    }
+    // entries to show, the commands named by the query if there are any
+    fn selected(&self) -> Vec<&HelpEntry> {
+        let query = self.query.to_lowercase();
+        let named = self.entries.iter()
+            .filter(|e| e.name == query || e.name.starts_with(&format!("{query} ")))
+            .collect::<Vec<_>>();
+        if !query.is_empty() && !named.is_empty() { return named }
+        self.entries.iter().filter(|e| e.matches(&query)).collect()
+    }
+    fn lines(&self) -> Vec<tui::text::Spans<'_>> {
+        use tui::text::*;
+        use tui::style::*;
+        let strong = Style::default().add_modifier(Modifier::BOLD);
+        let syntax = Style::default().fg(Color::Rgb(120, 200, 255));
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let selected = self.selected();
+        if selected.is_empty() {
+            return vec![Spans::from(Span::styled(format!("no command matches {}", self.query), faint))]
+        }
+        // a few commands are shown in detail, more are listed by syntax and summary
+        if self.query.is_empty() || selected.len() > 4 {
+            return selected.into_iter().map(|e| Spans::from(vec![
+                Span::styled(e.syntax.clone(), syntax),
+                Span::raw("  "),
+                Span::raw(e.summary.clone()),
+            ])).collect()
+        }
+        let mut lines = vec![];
+        for e in selected {
+            lines.push(Spans::from(vec![
+                Span::styled(e.name.clone(), strong),
+                Span::styled(format!("  ({})", e.origin), faint),
+            ]));
+            lines.push(Spans::from(Span::raw(e.summary.clone())));
+            lines.push(Spans::from(Span::styled(e.syntax.clone(), syntax)));
+            for (arg, about, regex) in e.args.iter() {
+                lines.push(Spans::from(vec![
+                    Span::styled(format!("  {arg}"), syntax),
+                    Span::raw(format!("  {about}")),
+                    Span::styled(format!("  {regex}"), faint),
+                ]));
+            }
+            for example in e.examples.iter() {
+                lines.push(Spans::from(vec![Span::styled("  e.g. ", faint), Span::raw(example.clone())]));
+            }
+            lines.push(Spans::default());
+        }
+        lines
+    }
+}
+
+impl Viewer for HelpView {
+    fn name(&self) -> String {
+        "help".to_string()
+    }
+    fn refresh(&mut self, _db: &crate::DataBase) {}
+    fn render(&self, f: &mut Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        let title = if self.query.is_empty() { String::from("help") } else { format!("help: {}", self.query) };
+        let block = Block::default().borders(Borders::ALL).title(title);
+        f.render_widget(Paragraph::new(self.lines()).block(block).wrap(Wrap { trim: false }), rect);
+    }
+}
@@ -0,0 +1,62 @@
+use super::*;
+use crate::app::history::{HistEntry, History};
+
+// at most this many commands are kept for display
+const MAX_SHOWN: usize = 256;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryView {
+    query: String,
+    // filled by the app from the global history
+    #[serde(skip)]
+    entries: Vec<HistEntry>,
+    #[serde(skip)]
+    tz: i64,
+}
+
+impl HistoryView {
+    pub fn new(query: impl Into<String>) -> Self {
+        HistoryView { query: query.into(), entries: vec![], tz: 0 }
+    }
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.query = query.into();
+    }
+    // most recent commands containing the query
+    pub fn set_entries(&mut self, history: &History, tz: i32) {
+        let query = self.query.to_lowercase();
+        self.entries = history.iter()
+            .filter(|e| e.command.to_lowercase().contains(&query))
+            .take(MAX_SHOWN).cloned().collect();
+        self.tz = tz as i64;
+    }
+}
+
+impl Viewer for HistoryView {
+    fn name(&self) -> String {
+        "history".to_string()
+    }
+    fn refresh(&mut self, _db: &crate::DataBase) {}
+    fn render(&self, f: &mut Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        use tui::style::*;
+        use tui::text::*;
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let ok = Style::default().fg(Color::Rgb(80, 200, 120));
+        let err = Style::default().fg(Color::Rgb(255, 90, 90));
+        let lines = self.entries.iter().map(|e| {
+            let time = chrono::NaiveDateTime::from_timestamp_opt(e.time() + self.tz * 60 * 60, 0).unwrap();
+            let mut spans = vec![
+                Span::styled(format!("{} pg{} x{:<3} ", time.format("%m/%d %H:%M"), e.page, e.runs), faint),
+                Span::raw(e.command.clone()),
+            ];
+            match &e.error {
+                None => spans.push(Span::styled("  ok", ok)),
+                Some(e) => spans.push(Span::styled(format!("  {e}"), err)),
+            }
+            Spans::from(spans)
+        }).collect::<Vec<_>>();
+        let title = if self.query.is_empty() { String::from("history") } else { format!("history: {}", self.query) };
+        let block = Block::default().borders(Borders::ALL).title(title);
+        f.render_widget(Paragraph::new(lines).block(block), rect);
+    }
+}
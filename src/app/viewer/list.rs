@@ -0,0 +1,255 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use crate::data::*;
+use crate::app::execute::{quote, tokenize};
+use crate::app::value;
+use super::{KeyResult, Viewer};
+use serde::*;
+
+// rows skipped by page up and page down
+const PAGE: usize = 10;
+
+// what the rows are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortKey {
+    Name,
+    // earliest first, rows without a due date last
+    Due,
+    // least done first
+    Progress,
+    // heaviest first
+    Weight,
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(SortKey::Name),
+            "due" => Ok(SortKey::Due),
+            "progress" => Ok(SortKey::Progress),
+            "weight" => Ok(SortKey::Weight),
+            _ => Err(format!("invalid sort key {s}, use name, due, progress or weight")),
+        }
+    }
+}
+
+impl std::fmt::Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SortKey::Name => "name",
+            SortKey::Due => "due",
+            SortKey::Progress => "progress",
+            SortKey::Weight => "weight",
+        })
+    }
+}
+
+// one condition of the filter, a row is listed if it meets all of them
+enum Term {
+    Name(regex::Regex),
+    Parent(String),
+    Due,
+    Overdue,
+    Type(WeightType),
+    Pj,
+    Ev,
+}
+
+impl Term {
+    // terms are separated by spaces, a parent with spaces is quoted like parent:'house move'
+    fn parse(filter: &str) -> Result<Vec<Term>, String> {
+        tokenize(filter)?.into_iter().map(|t| Ok(match t.as_str() {
+            "due" => Term::Due,
+            "overdue" => Term::Overdue,
+            "pj" | "project" => Term::Pj,
+            "ev" | "event" => Term::Ev,
+            "flex" | "flexible" | "rsv" | "reserved" => Term::Type(value::weight_type(&t)?),
+            _ => match t.strip_prefix("parent:") {
+                Some(pp) => Term::Parent(pp.to_string()),
+                // anything else is a regex on the name
+                None => Term::Name(regex::Regex::new(&t).map_err(|_| format!("invalid name regex {t}"))?),
+            },
+        })).collect()
+    }
+    fn meets(&self, row: &Entry) -> bool {
+        match self {
+            Term::Name(r) => r.is_match(&row.name),
+            Term::Parent(pp) => &row.parent == pp,
+            Term::Due => row.due.is_some(),
+            Term::Overdue => row.overdue,
+            Term::Type(t) => row.weight_type == Some(*t),
+            Term::Pj => !row.ev,
+            Term::Ev => row.ev,
+        }
+    }
+}
+
+// a project or an event, events have no quota done nor weight
+#[derive(Debug, Clone)]
+struct Entry {
+    ev: bool,
+    name: String,
+    parent: String,
+    color: tui::style::Color,
+    done: Option<usize>,
+    esti: usize,
+    // the deadline of a project, the time of an event
+    due: Option<i64>,
+    overdue: bool,
+    weight: Option<usize>,
+    weight_type: Option<WeightType>,
+}
+
+impl Entry {
+    fn progress(&self) -> Option<f64> {
+        self.done.filter(|_| self.esti > 0).map(|d| d as f64 / self.esti as f64)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListView {
+    filter: String,
+    sort: SortKey,
+    #[serde(default)]
+    cursor: usize,
+    #[serde(skip)]
+    rows: Vec<Entry>,
+    #[serde(skip)]
+    tz: i64,
+    #[serde(skip)]
+    focused: bool,
+}
+
+impl ListView {
+    pub fn new(filter: impl Into<String>, sort: SortKey) -> Result<Self, String> {
+        let mut list = ListView { filter: String::new(), sort, cursor: 0, rows: vec![], tz: 0, focused: false };
+        list.set(filter, sort)?;
+        Ok(list)
+    }
+    // the rows are rebuilt on the next refresh
+    pub fn set(&mut self, filter: impl Into<String>, sort: SortKey) -> Result<(), String> {
+        let filter = filter.into();
+        Term::parse(&filter)?;
+        self.filter = filter;
+        self.sort = sort;
+        self.cursor = 0;
+        Ok(())
+    }
+    fn rows(db: &crate::DataBase) -> Vec<Entry> {
+        let now = chrono::Utc::now().timestamp();
+        let name = |id: usize| db.pj_get_by_id(id).map(|pp| pp.name().to_string()).unwrap_or_default();
+        let mut rows = vec![];
+        for pj in db.pj_list().unwrap_or_default().iter().filter_map(|n| db.pj_get_by_name(n)) {
+            // the root holds everything and is not listed
+            if pj.id() == 0 { continue }
+            let overdue = pj.deadline().is_some_and(|d| d < now && pj.quota_done() < pj.quota_esti());
+            rows.push(Entry {
+                ev: false, name: pj.name().to_string(), parent: name(pj.pp()), color: pj.color_rgb(),
+                done: Some(pj.quota_done()), esti: pj.quota_esti(), due: pj.deadline(), overdue,
+                weight: Some(pj.weight()), weight_type: Some(pj.weight_type()),
+            });
+        }
+        for ev in db.ev_list().unwrap_or_default().iter().filter_map(|n| db.ev_get_by_name(n)) {
+            rows.push(Entry {
+                ev: true, name: ev.name().to_string(), parent: name(ev.pp()), color: ev.color_rgb(),
+                done: None, esti: ev.quota_esti(), due: Some(ev.time()), overdue: false,
+                weight: None, weight_type: None,
+            });
+        }
+        rows
+    }
+    fn sort(&mut self) {
+        // rows without the key go last, ties by name
+        self.rows.sort_by(|a, b| a.name.cmp(&b.name));
+        match self.sort {
+            SortKey::Name => {}
+            SortKey::Due => self.rows.sort_by_key(|r| (r.due.is_none(), r.due)),
+            SortKey::Progress => self.rows.sort_by(|a, b| match (a.progress(), b.progress()) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_none().cmp(&b.is_none()),
+            }),
+            SortKey::Weight => self.rows.sort_by_key(|r| (r.weight.is_none(), std::cmp::Reverse(r.weight))),
+        }
+    }
+    fn bar(row: &Entry) -> String {
+        match row.done {
+            Some(done) => super::quota_bar(done, row.esti),
+            None => format!("{:>10}h", row.esti),
+        }
+    }
+}
+
+impl Viewer for ListView {
+    fn name(&self) -> String {
+        "list".to_string()
+    }
+    fn refresh(&mut self, db: &crate::DataBase) {
+        let terms = Term::parse(&self.filter).unwrap_or_default();
+        self.rows = Self::rows(db).into_iter().filter(|r| terms.iter().all(|t| t.meets(r))).collect();
+        self.sort();
+        self.tz = db.tz() as i64;
+        self.cursor = self.cursor.min(self.rows.len().saturating_sub(1));
+    }
+    fn focus(&mut self, on: bool) -> bool {
+        self.focused = on;
+        true
+    }
+    fn key(&mut self, key: KeyEvent) -> KeyResult {
+        let len = self.rows.len();
+        if len == 0 { return KeyResult::Ignored }
+        match key.code {
+            KeyCode::Up => self.cursor = (self.cursor + len - 1) % len,
+            KeyCode::Down => self.cursor = (self.cursor + 1) % len,
+            KeyCode::PageUp => self.cursor = self.cursor.saturating_sub(PAGE),
+            KeyCode::PageDown => self.cursor = (self.cursor + PAGE).min(len - 1),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len - 1,
+            // open the selected row in the editor
+            KeyCode::Enter => {
+                let row = &self.rows[self.cursor];
+                return KeyResult::Command(format!("ed {} {}", if row.ev { "ev" } else { "pj" }, quote(&row.name)))
+            }
+            _ => return KeyResult::Ignored,
+        }
+        KeyResult::Consumed
+    }
+    fn render(&self, f: &mut super::Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        use tui::style::*;
+        use tui::layout::*;
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let err = Style::default().fg(Color::Rgb(255, 90, 90));
+        let rows = self.rows.iter().map(|r| {
+            let due = r.due.map(|d| chrono::NaiveDateTime::from_timestamp_opt(d + self.tz * 60 * 60, 0).unwrap()
+                .format("%Y/%m/%d %H:%M").to_string()).unwrap_or_default();
+            Row::new(vec![
+                Cell::from(if r.ev { "●" } else { "■" }).style(Style::default().fg(r.color)),
+                Cell::from(r.name.clone()),
+                Cell::from(r.parent.clone()).style(faint),
+                Cell::from(Self::bar(r)),
+                Cell::from(due).style(if r.overdue { err } else { Style::default() }),
+            ])
+        });
+        let header = Row::new(vec!["", "name", "parent", "progress", "due"]).style(faint);
+        let title = match (self.filter.is_empty(), self.focused) {
+            (true, false) => format!("list ({}) by {}", self.rows.len(), self.sort),
+            (false, false) => format!("list: {} ({}) by {}", self.filter, self.rows.len(), self.sort),
+            (_, true) => String::from("enter to edit, esc to leave"),
+        };
+        let mut border = Style::default();
+        if self.focused { border = border.add_modifier(Modifier::BOLD) }
+        let widths = [
+            Constraint::Length(1), Constraint::Percentage(40), Constraint::Percentage(20),
+            Constraint::Length(14), Constraint::Length(16),
+        ];
+        let table = Table::new(rows)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title(title).border_style(border))
+            .widths(&widths)
+            .highlight_style(Style::default().add_modifier(if self.focused { Modifier::REVERSED } else { Modifier::BOLD }));
+        // the table scrolls to keep the selected row in sight
+        let mut state = TableState::default();
+        state.select((!self.rows.is_empty()).then_some(self.cursor));
+        f.render_stateful_widget(table, rect, &mut state);
+    }
+}
@@ -1,16 +1,67 @@
 //! viewer plugins
 use serde::*;
+mod burn;
+mod calendar;
+mod canvas;
 mod color_block;
 mod editor;
+mod gantt;
+mod graph;
+mod heat;
+mod help;
+mod history;
+mod list;
+mod plan;
+mod tree;
+pub use burn::*;
+pub use calendar::*;
 pub use editor::*;
+pub use gantt::*;
+pub use graph::*;
 pub use color_block::*;
+pub use heat::*;
+pub use help::*;
+pub use history::*;
+pub use list::*;
+pub use plan::*;
+pub use tree::*;
 
 type Frame<'a> = tui::Frame<'a, tui::backend::CrosstermBackend<std::io::Stdout>>;
 
+// what a focused viewer did with a key
+pub enum KeyResult {
+    // not a key of this viewer
+    Ignored,
+    Consumed,
+    // a command line for the app to run, changes go through commands to be logged
+    Command(String),
+}
+
+// a bar of quota done over estimated, like ████░░░░ 4/8
+fn quota_bar(done: usize, esti: usize) -> String {
+    const W: usize = 8;
+    let full = match esti {
+        0 => 0,
+        _ => ((done as f64 / esti as f64).min(1.0) * W as f64).round() as usize,
+    };
+    format!("{}{} {done}/{esti}", "█".repeat(full), "░".repeat(W - full))
+}
+
 pub trait Viewer {
     fn name(&self) -> String;
     fn render(&self, f: &mut Frame, rect: tui::layout::Rect);
     fn refresh(&mut self, db: &crate::DataBase);
+    // take or lose the keyboard, false if the viewer does not take keys
+    fn focus(&mut self, _on: bool) -> bool { false }
+    fn key(&mut self, _key: crossterm::event::KeyEvent) -> KeyResult { KeyResult::Ignored }
+    // take what the viewer needs from the user configuration, before every refresh
+    fn set_config(&mut self, _config: &crate::config::Config) {}
+}
+
+// a viewer that is a variant of ViewerOpt, to find it among the viewers of a page
+pub trait Variant: Into<ViewerOpt> {
+    fn is(opt: &ViewerOpt) -> bool;
+    fn of(opt: &mut ViewerOpt) -> Option<&mut Self>;
 }
 
 macro_rules! declare_viewer_opt {
@@ -38,6 +89,24 @@ macro_rules! declare_viewer_opt {
                     $($Opt(x) => x.refresh(db), )*
                 }
             }
+            fn focus(&mut self, on: bool) -> bool {
+                use ViewerOpt::*;
+                match self {
+                    $($Opt(x) => x.focus(on), )*
+                }
+            }
+            fn key(&mut self, key: crossterm::event::KeyEvent) -> KeyResult {
+                use ViewerOpt::*;
+                match self {
+                    $($Opt(x) => x.key(key), )*
+                }
+            }
+            fn set_config(&mut self, config: &crate::config::Config) {
+                use ViewerOpt::*;
+                match self {
+                    $($Opt(x) => x.set_config(config), )*
+                }
+            }
         }
         $(
             impl Into<ViewerOpt> for $Opt {
@@ -45,12 +114,33 @@ macro_rules! declare_viewer_opt {
                     ViewerOpt::$Opt(self)
                 }
             }
+            impl Variant for $Opt {
+                fn is(opt: &ViewerOpt) -> bool {
+                    matches!(opt, ViewerOpt::$Opt(_))
+                }
+                fn of(opt: &mut ViewerOpt) -> Option<&mut Self> {
+                    match opt {
+                        ViewerOpt::$Opt(x) => Some(x),
+                        _ => None,
+                    }
+                }
+            }
         )*
     };
 }
 
 // do something like automatic dispatch, but make it serializable
 declare_viewer_opt!{
+    BurnView
+    CalView
     ColorBlock
     EditorView
+    GanttView
+    GraphView
+    HeatView
+    HelpView
+    HistoryView
+    ListView
+    PlanView
+    TreeView
 }
\ No newline at end of file
@@ -0,0 +1,239 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::style::Color;
+use crate::config::WeekStart;
+use crate::data::*;
+use super::{KeyResult, Viewer};
+use serde::*;
+
+// width of the hour labels
+const LABEL_W: u16 = 6;
+
+// an event or a proposal as drawn, in local time
+#[derive(Debug, Clone)]
+struct Block {
+    name: String,
+    color: Color,
+    start: NaiveDateTime,
+    quota: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanView {
+    // a local day of the week shown, in days from the common era
+    day: i32,
+    // utc starts of hours whose proposals were rejected, they stay free
+    #[serde(default)]
+    rejected: Vec<i64>,
+    #[serde(default)]
+    cursor: usize,
+    // set by the app from the config
+    #[serde(skip)]
+    week_start: WeekStart,
+    #[serde(skip)]
+    hours: (u32, u32),
+    #[serde(skip)]
+    events: Vec<Block>,
+    #[serde(skip)]
+    proposals: Vec<(Proposal, Block)>,
+    #[serde(skip)]
+    now: Option<NaiveDateTime>,
+    #[serde(skip)]
+    focused: bool,
+}
+
+impl PlanView {
+    pub fn new(date: NaiveDate) -> Self {
+        PlanView {
+            day: date.num_days_from_ce(), rejected: vec![], cursor: 0, week_start: WeekStart::default(), hours: (9, 18),
+            events: vec![], proposals: vec![], now: None, focused: false,
+        }
+    }
+    // another week, rejections are forgotten
+    pub fn set_date(&mut self, date: NaiveDate) {
+        self.day = date.num_days_from_ce();
+        self.rejected.clear();
+        self.cursor = 0;
+    }
+    pub fn shift(&mut self, weeks: i64) {
+        self.day += weeks as i32 * 7;
+        self.cursor = 0;
+    }
+    // proposals are numbered from 1 as shown
+    pub fn proposal(&self, n: usize) -> Option<&Proposal> {
+        self.proposals.get(n.wrapping_sub(1)).map(|(p, _)| p)
+    }
+    pub fn proposals(&self) -> impl Iterator<Item = &Proposal> {
+        self.proposals.iter().map(|(p, _)| p)
+    }
+    // keep the hours of a proposal free from now on
+    pub fn reject(&mut self, p: &Proposal) {
+        self.rejected.extend((0..p.quota as i64).map(|h| p.time + h * QUOTA_UNIT));
+    }
+    // the first day shown
+    fn first(&self) -> NaiveDate {
+        let date = NaiveDate::from_num_days_from_ce_opt(self.day).unwrap();
+        let back = (date.weekday().num_days_from_monday() + 7 - self.week_start.weekday().num_days_from_monday()) % 7;
+        date - Duration::days(back as i64)
+    }
+    // hours of the day shown, the working hours and every event of the week
+    fn span(&self) -> (u32, u32) {
+        let mut span = self.hours;
+        for b in self.events.iter().chain(self.proposals.iter().map(|(_, b)| b)) {
+            let end = b.start + Duration::hours(b.quota as i64);
+            span.0 = span.0.min(b.start.hour());
+            span.1 = span.1.max(if end.date() > b.start.date() { 24 } else { end.hour() + (end.minute() > 0) as u32 });
+        }
+        span
+    }
+    // the event name a proposal gets when accepted
+    pub fn event_name(db: &DataBase, p: &Proposal) -> String {
+        let pj = db.pj_get_by_id(p.pj).map(|pj| pj.name().to_string()).unwrap_or_default();
+        let local = NaiveDateTime::from_timestamp_opt(p.time + db.tz() as i64 * 60 * 60, 0).unwrap();
+        format!("{pj} {}", local.format("%m/%d %H:%M"))
+    }
+    // colorless events and projects would not show on a dark background
+    fn color(color: usize, or: Color) -> Color {
+        match color {
+            0 => or,
+            c => Color::Rgb((c >> 16) as u8, (c >> 8) as u8, c as u8),
+        }
+    }
+    fn render_block(&self, f: &mut super::Frame, area: tui::layout::Rect, rows: (u32, u16), b: &Block, style: tui::style::Style, label: String) {
+        use tui::widgets::*;
+        use tui::text::*;
+        use tui::layout::*;
+        let (first_hour, per_hour) = rows;
+        let day = (b.start.date() - self.first()).num_days();
+        if !(0..7).contains(&day) { return }
+        let w = (area.width - LABEL_W) / 7;
+        let offset = |t: NaiveDateTime| -> i64 {
+            let minutes = t.hour() as i64 * 60 + t.minute() as i64 - first_hour as i64 * 60;
+            minutes * per_hour as i64 / 60
+        };
+        let end = (b.start + Duration::hours(b.quota as i64)).min(b.start.date().and_hms_opt(23, 59, 59).unwrap());
+        let top = offset(b.start).max(0);
+        let bottom = offset(end).max(top + 1).min((area.height - 1) as i64);
+        if top >= bottom { return }
+        let rect = Rect {
+            x: area.x + LABEL_W + day as u16 * w, y: area.y + 1 + top as u16,
+            width: w.saturating_sub(1), height: (bottom - top) as u16,
+        };
+        let lines = (0..rect.height).map(|i| match i {
+            0 => Spans::from(Span::styled(format!("{label:<width$}", width = rect.width as usize), style)),
+            // solid blocks have a background, the others are hatched
+            _ => Spans::from(Span::styled(if style.bg.is_some() { " " } else { "░" }.repeat(rect.width as usize), style)),
+        }).collect::<Vec<_>>();
+        f.render_widget(Paragraph::new(lines), rect);
+    }
+}
+
+impl Viewer for PlanView {
+    fn name(&self) -> String {
+        "planner".to_string()
+    }
+    fn set_config(&mut self, config: &crate::config::Config) {
+        self.week_start = config.week_start;
+        self.hours = config.work_hours;
+    }
+    fn refresh(&mut self, db: &crate::DataBase) {
+        let tz = db.tz() as i64 * 60 * 60;
+        let local = |t: i64| NaiveDateTime::from_timestamp_opt(t + tz, 0).unwrap();
+        let first = self.first().and_hms_opt(0, 0, 0).unwrap().timestamp() - tz;
+        let last = first + 7 * 24 * 60 * 60;
+        let gray = Color::Rgb(127, 127, 127);
+        let pj_color = |id: usize| db.pj_get_by_id(id).map_or(gray, |pj| Self::color(pj.color_usize(), gray));
+        self.events = db.ev_list().unwrap_or_default().iter().filter_map(|n| db.ev_get_by_name(n))
+            .filter(|ev| (first - ev.quota_esti() as i64 * QUOTA_UNIT..last).contains(&ev.time()))
+            .map(|ev| Block {
+                name: ev.name().to_string(), color: Self::color(ev.color_usize(), pj_color(ev.pp())),
+                start: local(ev.time()), quota: ev.quota_esti(),
+            }).collect();
+        // the plan starts now, so weeks ahead take what earlier weeks leave
+        let now = chrono::Utc::now().timestamp();
+        self.proposals = db.auto_schedule(now, last, self.hours, &self.rejected).into_iter()
+            .filter(|p| p.time >= first)
+            .map(|p| {
+                let name = db.pj_get_by_id(p.pj).map(|pj| pj.name().to_string()).unwrap_or_default();
+                let block = Block { name, color: pj_color(p.pj), start: local(p.time), quota: p.quota };
+                (p, block)
+            }).collect();
+        self.now = Some(local(now));
+        self.cursor = self.cursor.min(self.proposals.len().saturating_sub(1));
+    }
+    fn focus(&mut self, on: bool) -> bool {
+        self.focused = on;
+        true
+    }
+    fn key(&mut self, key: KeyEvent) -> KeyResult {
+        let len = self.proposals.len();
+        match key.code {
+            // another week needs a new plan, which the refresh after a command makes
+            KeyCode::Left | KeyCode::PageUp => return KeyResult::Command(String::from("plan prev")),
+            KeyCode::Right | KeyCode::PageDown => return KeyResult::Command(String::from("plan next")),
+            KeyCode::Up if len > 0 => self.cursor = (self.cursor + len - 1) % len,
+            KeyCode::Down if len > 0 => self.cursor = (self.cursor + 1) % len,
+            // proposals become events through commands, so they can be undone
+            KeyCode::Enter if len > 0 => return KeyResult::Command(format!("plan accept {}", self.cursor + 1)),
+            KeyCode::Delete if len > 0 => return KeyResult::Command(format!("plan reject {}", self.cursor + 1)),
+            _ => return KeyResult::Ignored,
+        }
+        KeyResult::Consumed
+    }
+    fn render(&self, f: &mut super::Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        use tui::style::*;
+        use tui::text::*;
+        use tui::layout::*;
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let red = Style::default().fg(Color::Rgb(255, 90, 90));
+        let first = self.first();
+        let mut title = format!("week of {}, {} proposed", first.format("%Y/%m/%d"), self.proposals.len());
+        if self.focused { title.push_str(", enter accepts, del rejects, esc to leave") }
+        let mut border = Style::default();
+        if self.focused { border = border.add_modifier(Modifier::BOLD) }
+        let block = Block::default().borders(Borders::ALL).title(title).border_style(border);
+        let area = block.inner(rect);
+        f.render_widget(block, rect);
+        if area.width < LABEL_W + 7 || area.height < 2 { return }
+        let w = (area.width - LABEL_W) / 7;
+        // one header line, then as many lines an hour as fit
+        let (start, end) = self.span();
+        let per_hour = ((area.height - 1) as u32 / (end - start).max(1)).max(1) as u16;
+        for d in 0..7 {
+            let day = first + Duration::days(d);
+            let style = if Some(day) == self.now.map(|n| n.date()) { Style::default().add_modifier(Modifier::REVERSED) } else { faint };
+            let cell = Rect { x: area.x + LABEL_W + d as u16 * w, y: area.y, width: w, height: 1 };
+            f.render_widget(Paragraph::new(Span::styled(day.format("%a %m/%d").to_string(), style)), cell);
+        }
+        for hour in start..end {
+            let y = area.y + 1 + (hour - start) as u16 * per_hour;
+            if y >= area.bottom() { break }
+            let cell = Rect { x: area.x, y, width: LABEL_W, height: 1 };
+            f.render_widget(Paragraph::new(Span::styled(format!("{hour:02}:00"), faint)), cell);
+        }
+        // proposals are hatched in the color of their project, events are solid
+        for (i, (_, b)) in self.proposals.iter().enumerate() {
+            let mut style = Style::default().fg(b.color).add_modifier(Modifier::ITALIC);
+            if self.focused && i == self.cursor { style = style.add_modifier(Modifier::REVERSED) }
+            let label = format!("#{} {} {}h", i + 1, b.name, b.quota);
+            self.render_block(f, area, (start, per_hour), b, style, label);
+        }
+        for b in self.events.iter() {
+            let style = Style::default().bg(b.color).fg(Color::Black);
+            self.render_block(f, area, (start, per_hour), b, style, b.name.clone());
+        }
+        // the current time is a line across today
+        if let Some(now) = self.now {
+            let day = (now.date() - first).num_days();
+            let minutes = now.hour() as i64 * 60 + now.minute() as i64 - start as i64 * 60;
+            let row = minutes * per_hour as i64 / 60;
+            if (0..7).contains(&day) && (0..(area.height - 1) as i64).contains(&row) && now.hour() < end {
+                let y = area.y + 1 + row as u16;
+                f.render_widget(Paragraph::new(Span::styled("now", red)), Rect { x: area.x, y, width: LABEL_W, height: 1 });
+                let line = Rect { x: area.x + LABEL_W + day as u16 * w, y, width: w.saturating_sub(1), height: 1 };
+                f.render_widget(Paragraph::new(Span::styled("─".repeat(line.width as usize), red)), line);
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use unicode_width::UnicodeWidthStr;
+use crossterm::event::{KeyCode, KeyEvent};
+use crate::app::execute::quote;
+use super::{KeyResult, Viewer};
+use serde::*;
+
+// an item of the tree, projects and events have ids of their own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Node {
+    Pj(usize),
+    Ev(usize),
+}
+
+// a line of the tree
+#[derive(Debug, Clone)]
+struct Line {
+    node: Node,
+    depth: usize,
+    name: String,
+    color: tui::style::Color,
+    // projects with nothing below cannot be collapsed
+    leaf: bool,
+    // quota bar of a project, hours of an event
+    quota: String,
+    // deadline of a project, time of an event
+    due: String,
+    overdue: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TreeView {
+    root: usize,
+    // projects whose children are hidden
+    #[serde(default)]
+    collapsed: Vec<usize>,
+    // kept by node so it follows the selection when the tree changes
+    #[serde(default)]
+    selected: Option<Node>,
+    #[serde(skip)]
+    lines: Vec<Line>,
+    #[serde(skip)]
+    focused: bool,
+}
+
+impl TreeView {
+    pub fn new(root: usize) -> Self {
+        TreeView { root, collapsed: vec![], selected: None, lines: vec![], focused: false }
+    }
+    pub fn set_root(&mut self, root: usize) {
+        self.root = root;
+    }
+    pub fn selected(&self) -> Option<Node> {
+        self.selected
+    }
+    // lines not hidden under a collapsed project
+    fn visible(&self) -> Vec<&Line> {
+        let mut lines = vec![];
+        let mut hidden_below = None;
+        for l in self.lines.iter() {
+            match hidden_below {
+                Some(depth) if l.depth > depth => continue,
+                _ => hidden_below = None,
+            }
+            if self.is_open(l) == Some(false) { hidden_below = Some(l.depth) }
+            lines.push(l);
+        }
+        lines
+    }
+    // none for leaves, otherwise whether the children are shown
+    fn is_open(&self, l: &Line) -> Option<bool> {
+        match l.node {
+            Node::Pj(id) if !l.leaf => Some(!self.collapsed.contains(&id)),
+            _ => None,
+        }
+    }
+    fn cursor(&self) -> Option<usize> {
+        self.visible().iter().position(|l| Some(l.node) == self.selected)
+    }
+    fn select(&mut self, i: usize) {
+        self.selected = self.visible().get(i).map(|l| l.node);
+    }
+    fn set_open(&mut self, id: usize, open: bool) {
+        self.collapsed.retain(|&c| c != id);
+        if !open { self.collapsed.push(id) }
+    }
+    // lines of a project and everything below it
+    fn walk(&self, db: &crate::DataBase, id: usize, depth: usize, tz: i64, now: i64, lines: &mut Vec<Line>) {
+        let Some(pj) = db.pj_get_by_id(id) else { return };
+        let local = |t: i64| chrono::NaiveDateTime::from_timestamp_opt(t + tz, 0).unwrap();
+        let mut chpj = pj.iter_chpj().filter_map(|c| db.pj_get_by_id(c)).collect::<Vec<_>>();
+        chpj.sort_by(|a, b| a.name().cmp(b.name()));
+        let mut chev = pj.iter_chev().filter_map(|c| db.ev_get_by_id(c)).collect::<Vec<_>>();
+        chev.sort_by_key(|ev| ev.time());
+        lines.push(Line {
+            node: Node::Pj(id), depth, name: pj.name().to_string(), color: pj.color_rgb(),
+            leaf: chpj.is_empty() && chev.is_empty(),
+            quota: super::quota_bar(pj.quota_done(), pj.quota_esti()),
+            due: pj.deadline().map(|d| local(d).format("%Y/%m/%d").to_string()).unwrap_or_default(),
+            overdue: pj.deadline().is_some_and(|d| d < now && pj.quota_done() < pj.quota_esti()),
+        });
+        for ch in chpj {
+            self.walk(db, ch.id(), depth + 1, tz, now, lines);
+        }
+        for ev in chev {
+            lines.push(Line {
+                node: Node::Ev(ev.id()), depth: depth + 1, name: ev.name().to_string(), color: ev.color_rgb(),
+                leaf: true, quota: format!("{}h", ev.quota_esti()),
+                due: local(ev.time()).format("%Y/%m/%d %H:%M").to_string(), overdue: false,
+            });
+        }
+    }
+    // the parent of a visible line
+    fn parent(&self, i: usize) -> Option<usize> {
+        let lines = self.visible();
+        (0..i).rev().find(|&j| lines[j].depth < lines[i].depth)
+    }
+}
+
+impl Viewer for TreeView {
+    fn name(&self) -> String {
+        "tree".to_string()
+    }
+    fn refresh(&mut self, db: &crate::DataBase) {
+        // a deleted root falls back to the root of everything
+        if db.pj_get_by_id(self.root).is_none() { self.root = 0 }
+        let mut lines = vec![];
+        self.walk(db, self.root, 0, db.tz() as i64 * 60 * 60, chrono::Utc::now().timestamp(), &mut lines);
+        self.lines = lines;
+        // the selection stays on its node, or goes to the top if the node is gone
+        if self.cursor().is_none() { self.select(0) }
+    }
+    fn focus(&mut self, on: bool) -> bool {
+        self.focused = on;
+        true
+    }
+    fn key(&mut self, key: KeyEvent) -> KeyResult {
+        let Some(i) = self.cursor() else { return KeyResult::Ignored };
+        let len = self.visible().len();
+        let line = self.visible()[i].clone();
+        match (key.code, line.node, self.is_open(&line)) {
+            (KeyCode::Up, ..) => self.select((i + len - 1) % len),
+            (KeyCode::Down, ..) => self.select((i + 1) % len),
+            (KeyCode::Home, ..) => self.select(0),
+            (KeyCode::End, ..) => self.select(len - 1),
+            (KeyCode::PageUp, ..) => self.select(i.saturating_sub(10)),
+            (KeyCode::PageDown, ..) => self.select((i + 10).min(len - 1)),
+            // right opens a project, then goes to its first child
+            (KeyCode::Right, Node::Pj(id), Some(false)) => self.set_open(id, true),
+            (KeyCode::Right, _, Some(true)) => self.select(i + 1),
+            // left closes a project, then goes to its parent
+            (KeyCode::Left, Node::Pj(id), Some(true)) => self.set_open(id, false),
+            (KeyCode::Left, ..) => match self.parent(i) {
+                Some(p) => self.select(p),
+                None => return KeyResult::Ignored,
+            },
+            (KeyCode::Char(' '), Node::Pj(id), Some(open)) => self.set_open(id, !open),
+            (KeyCode::Enter, Node::Pj(_), _) => return KeyResult::Command(format!("ed pj {}", quote(&line.name))),
+            (KeyCode::Enter, Node::Ev(_), _) => return KeyResult::Command(format!("ed ev {}", quote(&line.name))),
+            _ => return KeyResult::Ignored,
+        }
+        KeyResult::Consumed
+    }
+    fn render(&self, f: &mut super::Frame, rect: tui::layout::Rect) {
+        use tui::widgets::*;
+        use tui::style::*;
+        use tui::text::*;
+        let faint = Style::default().fg(Color::Rgb(127, 127, 127));
+        let err = Style::default().fg(Color::Rgb(255, 90, 90));
+        let w = (rect.width as usize).saturating_sub(2);
+        let h = (rect.height as usize).saturating_sub(2);
+        let at = self.cursor().unwrap_or(0);
+        let lines = self.visible().into_iter().enumerate().skip((at + 1).saturating_sub(h)).take(h).map(|(i, l)| {
+            let marker = match self.is_open(l) { Some(true) => "▾ ", Some(false) => "▸ ", None => "  " };
+            let left = format!("{}{marker}{}", "  ".repeat(l.depth), l.name);
+            let right = format!(" {} {:>16}", l.quota, l.due);
+            let pad = w.saturating_sub(left.width() + right.width());
+            let mut name = Style::default();
+            if self.focused && i == at { name = name.add_modifier(Modifier::REVERSED) }
+            Spans::from(vec![
+                Span::styled(format!("{}{marker}", "  ".repeat(l.depth)), Style::default().fg(l.color)),
+                Span::styled(l.name.clone(), name),
+                Span::raw(" ".repeat(pad)),
+                Span::styled(format!(" {}", l.quota), faint),
+                Span::styled(format!(" {:>16}", l.due), if l.overdue { err } else { faint }),
+            ])
+        }).collect::<Vec<_>>();
+        let title = match self.focused {
+            true => "tree, space folds, enter edits, esc to leave",
+            false => "tree",
+        };
+        let mut border = Style::default();
+        if self.focused { border = border.add_modifier(Modifier::BOLD) }
+        let block = Block::default().borders(Borders::ALL).title(title).border_style(border);
+        f.render_widget(Paragraph::new(lines).block(block), rect);
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use serde::*;
+
+// user configuration, loaded from config.yaml
+// it is kept apart from data.yaml, tman only writes it back when aliases change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    // remote calendar collection for the sync command
+    #[serde(default)]
+    pub caldav: Option<crate::interop::caldav::CalDavConfig>,
+    // alias name -> macro body
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    // first day of the week in calendars
+    #[serde(default)]
+    pub week_start: WeekStart,
+    // local hours the planner fills, from the first to the last
+    #[serde(default = "Config::work_hours")]
+    pub work_hours: (u32, u32),
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { caldav: None, aliases: BTreeMap::new(), week_start: WeekStart::default(), work_hours: Config::work_hours() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl WeekStart {
+    pub fn weekday(self) -> chrono::Weekday {
+        use chrono::Weekday::*;
+        match self {
+            WeekStart::Monday => Mon,
+            WeekStart::Tuesday => Tue,
+            WeekStart::Wednesday => Wed,
+            WeekStart::Thursday => Thu,
+            WeekStart::Friday => Fri,
+            WeekStart::Saturday => Sat,
+            WeekStart::Sunday => Sun,
+        }
+    }
+}
+
+impl Config {
+    fn work_hours() -> (u32, u32) {
+        (9, 18)
+    }
+    pub fn load_yaml(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_reader::<_, Self>(std::fs::File::open(path)?)?)
+    }
+    pub fn save_yaml(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(serde_json::to_writer_pretty(std::fs::File::create(path)?, self)?)
+    }
+}
@@ -1,2 +1,73 @@
 use super::*;
+use std::collections::HashMap;
 
+// longest run of hours given to one project before others get a turn
+const MAX_BLOCK: usize = 2;
+
+// a block of time the scheduler suggests for a project, not an event yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal {
+    pub pj: usize,
+    // utc start
+    pub time: i64,
+    // hours, contiguous from time
+    pub quota: usize,
+}
+
+impl DataBase {
+    // fill free working hours between from and to with the quota projects still need
+    // hours are local hours of the day like (9, 18), busy holds starts of hours to keep free
+    // a project waits for its dependencies to be planned, then the earliest deadline goes first,
+    // reserved projects before flexible ones, and the rest is shared by weight
+    pub fn auto_schedule(&self, from: i64, to: i64, hours: (u32, u32), busy: &[i64]) -> Vec<Proposal> {
+        let tz = self.tz as i64 * 60 * 60;
+        let evs = self.ev_list().unwrap_or_default().iter()
+            .filter_map(|n| self.ev_get_by_name(n)).collect::<Vec<_>>();
+        let pjs = self.pj_list().unwrap_or_default().iter()
+            .filter_map(|n| self.pj_get_by_name(n)).filter(|pj| pj.id() != 0).collect::<Vec<_>>();
+        // hours left once done quota and events still to come are counted
+        let mut left = pjs.iter().map(|pj| {
+            let planned = evs.iter().filter(|ev| ev.pp() == pj.id() && ev.time() >= from)
+                .map(|ev| ev.quota_esti()).sum::<usize>();
+            (pj.id(), pj.quota_esti().saturating_sub(pj.quota_done() + planned))
+        }).collect::<HashMap<_, _>>();
+        let mut given = HashMap::<usize, usize>::new();
+        // only events that overlap the span can take its hours
+        let taken = evs.iter().map(|ev| (ev.time(), ev.time() + (ev.quota_esti() as i64) * QUOTA_UNIT))
+            .filter(|&(start, end)| end > from && start < to).collect::<Vec<_>>();
+        let free = |t: i64| {
+            let local = (t + tz).rem_euclid(24 * 60 * 60) / QUOTA_UNIT;
+            (hours.0 as i64..hours.1 as i64).contains(&local) && !busy.contains(&t)
+                && taken.iter().all(|&(start, end)| end <= t || t + QUOTA_UNIT <= start)
+        };
+        let mut proposals: Vec<Proposal> = vec![];
+        // (project, hours in a row) of the previous hour if it was given
+        let mut run: Option<(usize, usize)> = None;
+        let mut t = from + (QUOTA_UNIT - from.rem_euclid(QUOTA_UNIT)) % QUOTA_UNIT;
+        while t + QUOTA_UNIT <= to {
+            if !free(t) { run = None; t += QUOTA_UNIT; continue }
+            let ready = |pj: &&Pj| left[&pj.id()] > 0 && pj.iter_deps().all(|d| left.get(&d).copied().unwrap_or(0) == 0);
+            // a project keeps the next hour until its block is long enough
+            let pick = match run {
+                Some((id, n)) if n < MAX_BLOCK && left[&id] > 0 => pjs.iter().find(|pj| pj.id() == id),
+                _ => pjs.iter().filter(ready).min_by(|a, b| {
+                    let share = |pj: &Pj| given.get(&pj.id()).copied().unwrap_or(0) as f64 / pj.weight().max(1) as f64;
+                    (a.deadline().is_none(), a.deadline(), a.weight_type() != WeightType::Reserved)
+                        .cmp(&(b.deadline().is_none(), b.deadline(), b.weight_type() != WeightType::Reserved))
+                        .then(share(a).total_cmp(&share(b)))
+                        .then(a.id().cmp(&b.id()))
+                }),
+            };
+            // readiness only changes when hours are given, so no later hour finds anything either
+            let Some(pj) = pick else { break };
+            *left.get_mut(&pj.id()).unwrap() -= 1;
+            *given.entry(pj.id()).or_default() += 1;
+            match (run, proposals.last_mut()) {
+                (Some((id, n)), Some(last)) if id == pj.id() => { last.quota += 1; run = Some((id, n + 1)) }
+                _ => { proposals.push(Proposal { pj: pj.id(), time: t, quota: 1 }); run = Some((pj.id(), 1)) }
+            }
+            t += QUOTA_UNIT;
+        }
+        proposals
+    }
+}
@@ -12,6 +12,9 @@ pub struct Ev {
 }
 
 impl Ev {
+    pub fn new(name: String, pp: usize, time: i64, quota_esti: usize) -> Self {
+        Ev { id: 0usize, pp, name, time, quota_esti, color: (0, 0, 0) }
+    }
     pub fn id(&self) -> usize {
         self.id
     }
@@ -52,6 +55,18 @@ pub enum EvErr {
     InvalidEventId,
 }
 
+impl std::fmt::Display for EvErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use EvErr::*;
+        match self {
+            TimeClash => write!(f, "another event starts at the same time"),
+            NameClash => write!(f, "another event has this name"),
+            ParentNotExist => write!(f, "the parent project does not exist"),
+            InvalidEventId => write!(f, "the event does not exist"),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub enum EvLog {
     Create{id: usize},
@@ -77,6 +92,8 @@ impl EvStore {
             match pj_store.add_event(ev.pp, ev.id) {
                 Ok(log) => {
                     let id = ev.id;
+                    self.time.insert(ev.time, id);
+                    self.name.insert(ev.name.clone(), id);
                     self.vect.push(Some(ev));
                     Ok((EvLog::Create { id }, log))
                 },
@@ -155,4 +172,40 @@ impl EvStore {
             Ok(EvLog::Time { id, old })
         }
     }
+    pub fn undo(&mut self, log: EvLog, pj_store: &mut super::pj::PjStore) {
+        match log {
+            EvLog::Create { id } => {
+                if id == self.vect.len() - 1 {
+                    let ev = self.vect.pop().unwrap().unwrap();
+                    self.time.remove(&ev.time);
+                    self.name.remove(&ev.name);
+                } else { unreachable!() }
+            }
+            EvLog::Delete { old } => {
+                pj_store.add_event(old.pp, old.id).unwrap();
+                self.time.insert(old.time, old.id);
+                self.name.insert(old.name.clone(), old.id);
+                let id = old.id;
+                self.vect[id] = Some(old);
+            }
+            EvLog::Parent { id, old } => {
+                let new = std::mem::replace(&mut self.vect[id].as_mut().unwrap().pp, old);
+                pj_store.rmv_event(new, id).unwrap();
+                pj_store.add_event(old, id).unwrap();
+            }
+            EvLog::Name { id, old } => {
+                let new = std::mem::replace(&mut self.vect[id].as_mut().unwrap().name, old.clone());
+                self.name.remove(&new);
+                self.name.insert(old, id);
+            }
+            EvLog::Time { id, old } => {
+                let new = std::mem::replace(&mut self.vect[id].as_mut().unwrap().time, old);
+                self.time.remove(&new);
+                self.time.insert(old, id);
+            }
+            EvLog::QuotaEsti { id, old } => {
+                self.vect[id].as_mut().unwrap().quota_esti = old;
+            }
+        }
+    }
 }
\ No newline at end of file
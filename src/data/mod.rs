@@ -1,12 +1,18 @@
 mod ev;
 mod pj;
 mod auto_schedule;
+mod quota_log;
 
 pub use ev::*;
 pub use pj::*;
+pub use auto_schedule::*;
+pub use quota_log::*;
 use serde::*;
 use chrono::{NaiveDateTime, TimeZone};
 
+// length of one unit of quota in seconds
+pub const QUOTA_UNIT: i64 = 60 * 60;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DBLog {
     Ev(ev::EvLog),
@@ -27,12 +33,24 @@ pub enum DBErr {
     Ev(ev::EvErr),
 }
 
+impl std::fmt::Display for DBErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DBErr::Pj(e) => e.fmt(f),
+            DBErr::Ev(e) => e.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataBase {
     tz: i32,
     ev: ev::EvStore,
     pj: pj::PjStore,
     log: Vec<Vec<DBLog>>,
+    // utc time of each step of the log, 0 for steps logged before times were kept
+    #[serde(default)]
+    log_time: Vec<i64>,
 }
 
 impl DataBase {
@@ -40,11 +58,14 @@ impl DataBase {
         Self {
             ev: ev::EvStore::new(),
             pj: pj::PjStore::new(),
-            tz, log: Vec::new(),
+            tz, log: Vec::new(), log_time: Vec::new(),
         }
     }
     pub fn load_yaml(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(serde_json::from_reader::<_, Self>(std::fs::File::open(path)?)?)
+        let mut db = serde_json::from_reader::<_, Self>(std::fs::File::open(path)?)?;
+        let unknown = db.log.len().saturating_sub(db.log_time.len());
+        db.log_time.splice(0..0, std::iter::repeat_n(0, unknown));
+        Ok(db)
     }
     pub fn save_yaml(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
         Ok(serde_json::to_writer(std::fs::File::create(path)?, self)?)
@@ -62,7 +83,7 @@ impl DataBase {
         self.pj.get_by_name(name).unwrap_or_else(|| {
             let pj = pj::Pj::new(name.to_string());
             let res = self.pj.create(pj);
-            self.log.push(vec![res.unwrap().into()]);
+            self.push_log(vec![res.unwrap().into()]);
             self.pj.get_by_name(name).unwrap()
         })
     }
@@ -77,11 +98,118 @@ impl DataBase {
     }
     pub fn pj_set_name(&mut self, id: usize, name: String) -> Result<(), DBErr> {
         let log = self.pj.update_name(id, name).map_err(DBErr::Pj)?;
-        self.log.push(vec![DBLog::Pj(log)]); Ok(())
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_create(&mut self, name: &str, pp: usize) -> Result<usize, DBErr> {
+        self.pj.check_exists(pp).map_err(DBErr::Pj)?;
+        let log = self.pj.create(pj::Pj::new(name.to_string())).map_err(DBErr::Pj)?;
+        let id = match log { pj::PjLog::Create { id } => id, _ => unreachable!() };
+        let mut logs = vec![DBLog::Pj(log)];
+        if pp != 0 { logs.push(DBLog::Pj(self.pj.update_pp(id, pp).unwrap())) }
+        self.push_log(logs); Ok(id)
+    }
+    pub fn pj_delete(&mut self, id: usize) -> Result<(), DBErr> {
+        let log = self.pj.delete(id).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_set_pp(&mut self, id: usize, pp: usize) -> Result<(), DBErr> {
+        let log = self.pj.update_pp(id, pp).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_set_weight(&mut self, id: usize, weight: usize) -> Result<(), DBErr> {
+        let log = self.pj.update_weight(id, weight).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_set_weight_type(&mut self, id: usize, weight_type: WeightType) -> Result<(), DBErr> {
+        let log = self.pj.update_weight_type(id, weight_type).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_set_color(&mut self, id: usize, color: (u8, u8, u8)) -> Result<(), DBErr> {
+        let log = self.pj.update_color(id, color).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_set_deadline(&mut self, id: usize, deadline: Option<i64>) -> Result<(), DBErr> {
+        let log = self.pj.update_deadline(id, deadline).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_set_quota_esti(&mut self, id: usize, quota: usize) -> Result<(), DBErr> {
+        let log = self.pj.update_quota_esti(id, quota).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_set_quota_done(&mut self, id: usize, quota: usize) -> Result<(), DBErr> {
+        let log = self.pj.update_quota_done(id, quota).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_add_deps(&mut self, id: usize, dep: usize) -> Result<(), DBErr> {
+        let log = self.pj.add_deps(id, dep).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
+    }
+    pub fn pj_rmv_deps(&mut self, id: usize, dep: usize) -> Result<(), DBErr> {
+        let log = self.pj.rmv_deps(id, dep).map_err(DBErr::Pj)?;
+        self.push_log(vec![DBLog::Pj(log)]); Ok(())
     }
     pub fn ev_set_name(&mut self, id: usize, name: String) -> Result<(), DBErr> {
         let log = self.ev.update_name(id, name).map_err(DBErr::Ev)?;
-        self.log.push(vec![DBLog::Ev(log)]); Ok(())
+        self.push_log(vec![DBLog::Ev(log)]); Ok(())
+    }
+    pub fn ev_create(&mut self, ev: Ev) -> Result<usize, DBErr> {
+        let (ev_log, pj_log) = self.ev.create(ev, &mut self.pj).map_err(DBErr::Ev)?;
+        let id = match ev_log { ev::EvLog::Create { id } => id, _ => unreachable!() };
+        self.push_log(vec![DBLog::Ev(ev_log), DBLog::Pj(pj_log)]); Ok(id)
+    }
+    pub fn ev_delete(&mut self, id: usize) -> Result<(), DBErr> {
+        let log = self.ev.delete(id, &mut self.pj).map_err(DBErr::Ev)?;
+        self.push_log(vec![DBLog::Ev(log)]); Ok(())
+    }
+    pub fn ev_set_time(&mut self, id: usize, time: i64) -> Result<(), DBErr> {
+        let log = self.ev.update_time(id, time).map_err(DBErr::Ev)?;
+        self.push_log(vec![DBLog::Ev(log)]); Ok(())
+    }
+    pub fn ev_set_quota_esti(&mut self, id: usize, quota: usize) -> Result<(), DBErr> {
+        let log = self.ev.update_quota_esti(id, quota).map_err(DBErr::Ev)?;
+        self.push_log(vec![DBLog::Ev(log)]); Ok(())
+    }
+    pub fn ev_set_pp(&mut self, id: usize, pp: usize) -> Result<(), DBErr> {
+        let log = self.ev.update_pp(id, pp, &mut self.pj).map_err(DBErr::Ev)?;
+        self.push_log(vec![DBLog::Ev(log)]); Ok(())
+    }
+    fn push_log(&mut self, logs: Vec<DBLog>) {
+        self.log.push(logs);
+        self.log_time.push(chrono::Utc::now().timestamp());
+    }
+    // run f and merge everything it logged into one undoable step
+    pub fn transaction<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.merge(f).0
+    }
+    // like transaction, but the merged step is undone if f fails
+    pub fn atomic<T, E>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, E>) -> Result<T, E> {
+        let (res, merged) = self.merge(f);
+        if res.is_err() && merged { self.undo(); }
+        res
+    }
+    // run f and merge what it logged, return whether there was anything to merge
+    fn merge<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> (T, bool) {
+        let len = self.log.len();
+        let res = f(self);
+        // f may have undone steps logged before it
+        let len = len.min(self.log.len());
+        let logs = self.log.drain(len..).flatten().collect::<Vec<_>>();
+        self.log_time.truncate(len);
+        let merged = !logs.is_empty();
+        if merged { self.push_log(logs) }
+        (res, merged)
+    }
+    // revert the last logged step, return false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(logs) = self.log.pop() else { return false };
+        self.log_time.pop();
+        for log in logs.into_iter().rev() {
+            match log {
+                DBLog::Pj(log) => self.pj.undo(log),
+                DBLog::Ev(log) => self.ev.undo(log, &mut self.pj),
+            }
+        }
+        true
     }
     pub fn set_tz(&mut self, tz: i32) {
         assert!(tz >= -12 && tz <= 12);
@@ -80,7 +80,7 @@ impl Pj {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Copy, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, Default, PartialEq, Eq)]
 pub enum WeightType {
     Flexible,
     #[default]
@@ -107,6 +107,7 @@ pub enum PjLog {
     Parent{id: usize, old: usize},
     Weight{id: usize, old: usize},
     WeightType{id: usize, old: WeightType},
+    Color{id: usize, old: (u8, u8, u8)},
     Compact{idmap: Vec<usize>, length: usize},    // id to original position
     AddEvent{id: usize, ev: usize},
     RmvEvent{id: usize, ev: usize},
@@ -122,6 +123,30 @@ pub enum PjErr {
     NewProjectButPeerDependency,
     NewProjectButChildren,
     InvalidProjectId,
+    RootCannotBeDeleted,
+    ParentIsDescendant,
+    DependencyCycle,
+    NotADependency,
+}
+
+impl std::fmt::Display for PjErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use PjErr::*;
+        match self {
+            NameOfRootCannotBeModified => write!(f, "the root project cannot be renamed"),
+            NameNotDistinct => write!(f, "another project has this name"),
+            DependencyNotInPeer => write!(f, "a project can only depend on projects of the same parent"),
+            BannedByPeerDependency => write!(f, "the project has dependencies, remove them first"),
+            BannedByChildren => write!(f, "the project has children, remove or move them first"),
+            NewProjectButPeerDependency => write!(f, "a new project cannot have dependencies"),
+            NewProjectButChildren => write!(f, "a new project cannot have children"),
+            InvalidProjectId => write!(f, "the project does not exist"),
+            RootCannotBeDeleted => write!(f, "the root project cannot be deleted"),
+            ParentIsDescendant => write!(f, "a project cannot be moved into its own subtree"),
+            DependencyCycle => write!(f, "the dependency would make a cycle"),
+            NotADependency => write!(f, "the project is not a dependency"),
+        }
+    }
 }
 
 impl PjStore {
@@ -139,6 +164,8 @@ impl PjStore {
             Err(PjErr::NewProjectButChildren)?
         } else if pj.pp >= self.vect.len() {
             Err(PjErr::InvalidProjectId)?
+        } else if self.name.contains_key(&pj.name) {
+            Err(PjErr::NameNotDistinct)?
         } else {
             let id = self.vect.len();
             pj.id = id;
@@ -168,12 +195,19 @@ impl PjStore {
     pub fn delete(&mut self, id: usize) -> Result<PjLog, PjErr> {
         self.check_exists(id)?;
         let pj = self.vect[id].as_ref().unwrap();
-        if !pj.deps.is_empty() || !pj.deps_rvs.is_empty() {
+        if id == 0 {
+            Err(PjErr::RootCannotBeDeleted)
+        } else if !pj.deps.is_empty() || !pj.deps_rvs.is_empty() {
             Err(PjErr::BannedByPeerDependency)
         } else if !pj.chev.is_empty() || !pj.chpj.is_empty() {
             Err(PjErr::BannedByChildren)
         } else {
             let pj = std::mem::replace(&mut self.vect[id], None).expect("already deleted");
+            self.vect[pj.pp].as_mut().unwrap().chpj.remove(&id);
+            self.name.remove(&pj.name);
+            if let Some(deadline) = pj.deadline {
+                self.time.get_mut(&deadline).unwrap().remove(&id);
+            }
             Ok(PjLog::Delete { pj })
         }
     }
@@ -206,6 +240,8 @@ impl PjStore {
             Err(PjErr::BannedByPeerDependency)
         } else if pp >= self.vect.len() || self.vect[pp].is_none() {
             Err(PjErr::InvalidProjectId)
+        } else if self.is_descendant(pp, id) {
+            Err(PjErr::ParentIsDescendant)
         } else {
             let old = std::mem::replace(&mut self.vect[id].as_mut().unwrap().pp, pp);
             self.vect[old].as_mut().unwrap().chpj.remove(&id);
@@ -213,6 +249,19 @@ impl PjStore {
             Ok(PjLog::Parent { id, old })
         }
     }
+    // if x is y or lies in the subtree of y
+    pub fn is_descendant(&self, mut x: usize, y: usize) -> bool {
+        loop {
+            if x == y { return true }
+            if x == 0 { return false }
+            x = self.vect[x].as_ref().unwrap().pp;
+        }
+    }
+    pub fn update_color(&mut self, id: usize, color: (u8, u8, u8)) -> Result<PjLog, PjErr> {
+        self.check_exists(id)?;
+        let old = std::mem::replace(&mut self.vect[id].as_mut().unwrap().color, color);
+        Ok(PjLog::Color { id, old })
+    }
     pub fn update_name(&mut self, id: usize, name: String) -> Result<PjLog, PjErr> {
         self.check_exists(id)?;
         if &self.vect[id].as_ref().unwrap().name != &name && 
@@ -229,21 +278,36 @@ impl PjStore {
         }
     }
     pub fn add_deps(&mut self, id: usize, dep: usize) -> Result<PjLog, PjErr> {
-        // fix me: add loop detection
         self.check_exists(id)?;
         let pp = self.vect[id].as_ref().unwrap().pp;
         let peers = &self.vect[pp].as_ref().unwrap().chpj;
         if !peers.contains(&dep) {
             Err(PjErr::DependencyNotInPeer)
+        } else if self.depends_on(dep, id) {
+            Err(PjErr::DependencyCycle)
         } else {
             self.vect[id].as_mut().unwrap().deps.insert(dep);
             self.vect[dep].as_mut().unwrap().deps_rvs.insert(id);
             Ok(PjLog::DepsAdd { id, add: dep })
         }
     }
+    // if x is y or depends on y through a chain of dependencies
+    pub fn depends_on(&self, x: usize, y: usize) -> bool {
+        let mut stack = vec![x];
+        let mut seen = HashSet::new();
+        while let Some(x) = stack.pop() {
+            if x == y { return true }
+            if !seen.insert(x) { continue }
+            stack.extend(self.vect[x].as_ref().unwrap().deps.iter().copied());
+        }
+        false
+    }
     pub fn rmv_deps(&mut self, id: usize, dep: usize) -> Result<PjLog, PjErr> {
         self.check_exists(id)?;
         self.check_exists(dep)?;
+        if !self.vect[id].as_ref().unwrap().deps.contains(&dep) {
+            Err(PjErr::NotADependency)?
+        }
         self.vect[id].as_mut().unwrap().deps.remove(&dep);
         self.vect[dep].as_mut().unwrap().deps_rvs.remove(&id);
         Ok(PjLog::DepsRmv { id, rmv: dep })
@@ -316,6 +380,9 @@ impl PjStore {
             PjLog::WeightType { id, old } => {
                 self.vect[id].as_mut().unwrap().weight_type = old;
             }
+            PjLog::Color { id, old } => {
+                self.vect[id].as_mut().unwrap().color = old;
+            }
             PjLog::Create { id } => {
                 if id == self.vect.len() - 1 {
                     self.delete(id).unwrap();
@@ -326,6 +393,12 @@ impl PjStore {
                 self.vect[pj.id] = Some(pj.clone());
                 let pp = pj.pp;
                 self.vect[pp].as_mut().unwrap().chpj.insert(pj.id);
+                self.name.insert(pj.name.clone(), pj.id);
+                if let Some(deadline) = pj.deadline {
+                    self.time.entry(deadline)
+                        .and_modify(|ent| { ent.insert(pj.id); })
+                        .or_insert(HashSet::from([pj.id]));
+                }
                 for dep in pj.deps {
                     self.vect[dep].as_mut().unwrap().deps_rvs.insert(pj.id);
                 }
@@ -341,7 +414,7 @@ impl PjStore {
                 self.vect[id].as_mut().unwrap().quota_esti = old_esti;
             }
             PjLog::Deadline { id, old } => {
-                let new = self.vect[id].as_ref().unwrap().deadline;
+                let new = std::mem::replace(&mut self.vect[id].as_mut().unwrap().deadline, old);
                 if let Some(new) = new {
                     self.time.get_mut(&new)
                         .unwrap().remove(&id);
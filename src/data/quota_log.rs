@@ -0,0 +1,50 @@
+use super::*;
+
+// the quota of a project from a time on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaChange {
+    // utc, 0 if it was so before the times of the log are known
+    pub time: i64,
+    pub esti: usize,
+    pub done: usize,
+}
+
+impl DataBase {
+    // every change of the quota of a project read back from the log, oldest first
+    // the first one is its creation, or the state before the oldest step with a known time
+    pub fn pj_quota_log(&self, id: usize) -> Vec<QuotaChange> {
+        let Some(pj) = self.pj_get_by_id(id) else { return vec![] };
+        let (mut esti, mut done) = (pj.quota_esti(), pj.quota_done());
+        let mut changes = vec![];
+        for (logs, &time) in self.log.iter().zip(self.log_time.iter()).rev() {
+            if time == 0 { break }
+            for log in logs.iter().rev() {
+                match *log {
+                    DBLog::Pj(PjLog::QuotaEsti { id: i, old }) if i == id => {
+                        changes.push(QuotaChange { time, esti, done });
+                        esti = old;
+                    }
+                    DBLog::Pj(PjLog::QuotaDone { id: i, old_done, old_esti }) if i == id => {
+                        changes.push(QuotaChange { time, esti, done });
+                        (esti, done) = (old_esti, old_done);
+                    }
+                    // an id is only taken again after the project it was given to is gone
+                    DBLog::Pj(PjLog::Create { id: i }) if i == id => {
+                        changes.push(QuotaChange { time, esti, done });
+                        return Self::squash(changes);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        changes.push(QuotaChange { time: 0, esti, done });
+        Self::squash(changes)
+    }
+    // newest first to oldest first, with one change a step and none that kept the quota as it was
+    fn squash(mut changes: Vec<QuotaChange>) -> Vec<QuotaChange> {
+        changes.dedup_by_key(|c| c.time);
+        changes.reverse();
+        changes.dedup_by_key(|c| (c.esti, c.done));
+        changes
+    }
+}
@@ -0,0 +1,677 @@
+//! two-way synchronization of events with a caldav collection
+use std::collections::{HashMap, HashSet};
+use serde::*;
+use crate::data::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalDavConfig {
+    // url of the calendar collection
+    pub url: String,
+    // user name for basic authentication
+    pub user: String,
+    // password, overridden by TMAN_CALDAV_PASSWORD when it is set
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl CalDavConfig {
+    fn password(&self) -> String {
+        std::env::var("TMAN_CALDAV_PASSWORD").ok()
+            .or_else(|| self.password.clone())
+            .unwrap_or_default()
+    }
+}
+
+// what we knew about a remote object on the last synchronization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncEntry {
+    uid: String,
+    ev: usize,
+    etag: String,
+    // fingerprint of the local event on the last synchronization
+    print: String,
+}
+
+// synchronization state, stored apart from data.yaml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    // collection url this state belongs to
+    url: String,
+    // remote object path -> entry
+    href: HashMap<String, SyncEntry>,
+}
+
+impl SyncState {
+    pub fn load_yaml(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_reader::<_, Self>(std::fs::File::open(path)?)?)
+    }
+    pub fn save_yaml(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(serde_json::to_writer(std::fs::File::create(path)?, self)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SyncErr {
+    NotConfigured,
+    Transport(String),
+    Status(u16, String),
+}
+
+impl std::fmt::Display for SyncErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncErr::NotConfigured => write!(f, "caldav is not configured in config.yaml"),
+            SyncErr::Transport(e) => write!(f, "caldav transport error: {e}"),
+            SyncErr::Status(code, url) => write!(f, "caldav server replied {code} for {url}"),
+        }
+    }
+}
+
+// an item that was left untouched because both sides disagree
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub name: String,
+    pub href: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    pub pulled: usize,
+    pub pushed: usize,
+    pub removed_local: usize,
+    pub removed_remote: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl SyncReport {
+    pub fn summary(&self) -> String {
+        let mut s = format!(
+            "pulled {}, pushed {}, removed {} local, removed {} remote, {} conflicts",
+            self.pulled, self.pushed, self.removed_local, self.removed_remote, self.conflicts.len());
+        for c in self.conflicts.iter() {
+            s.push_str(&format!("; {} ({}): {}", c.name, c.href, c.reason));
+        }
+        s
+    }
+}
+
+// event as it is stored on the server
+#[derive(Debug, Clone)]
+struct RemoteEv {
+    uid: String,
+    name: String,
+    time: i64,
+    quota: usize,
+    parent: Option<String>,
+}
+
+// a listed remote object
+struct Remote {
+    href: String,
+    etag: String,
+    data: String,
+}
+
+enum PutErr {
+    Precondition,
+    Other(SyncErr),
+}
+
+pub struct CalDavClient {
+    agent: ureq::Agent,
+    config: CalDavConfig,
+}
+
+impl CalDavClient {
+    pub fn new(config: CalDavConfig) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(std::time::Duration::from_secs(30)).build();
+        Self { agent, config }
+    }
+    // origin of the collection url, e.g. https://dav.example.com
+    fn origin(&self) -> &str {
+        let url = &self.config.url;
+        let from = url.find("://").map(|i| i + 3).unwrap_or(0);
+        match url[from..].find('/') {
+            Some(i) => &url[..from + i],
+            None => url,
+        }
+    }
+    // path of the collection, always ending with a slash
+    fn path(&self) -> String {
+        let path = &self.config.url[self.origin().len()..];
+        format!("/{}/", path.trim_matches('/')).replace("//", "/")
+    }
+    // normalize a href from the server to a path
+    fn to_path(&self, href: &str) -> String {
+        if href.starts_with("http://") || href.starts_with("https://") {
+            let from = href.find("://").unwrap() + 3;
+            href[from..].find('/').map(|i| href[from + i..].to_string()).unwrap_or(String::from("/"))
+        } else { href.to_string() }
+    }
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        use base64::Engine;
+        let auth = format!("{}:{}", self.config.user, self.config.password());
+        let auth = base64::engine::general_purpose::STANDARD.encode(auth);
+        self.agent.request(method, &format!("{}{}", self.origin(), path))
+            .set("Authorization", &format!("Basic {auth}"))
+    }
+    // list all events in the collection with their etags
+    fn list(&self) -> Result<Vec<Remote>, SyncErr> {
+        let body = concat!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>"#,
+            r#"<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">"#,
+            r#"<d:prop><d:getetag/><c:calendar-data/></d:prop>"#,
+            r#"<c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT"/></c:comp-filter></c:filter>"#,
+            r#"</c:calendar-query>"#);
+        let path = self.path();
+        let res = self.request("REPORT", &path)
+            .set("Depth", "1")
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(body);
+        let xml = match res {
+            Ok(res) => res.into_string().map_err(|e| SyncErr::Transport(e.to_string()))?,
+            Err(ureq::Error::Status(code, _)) => Err(SyncErr::Status(code, path))?,
+            Err(e) => Err(SyncErr::Transport(e.to_string()))?,
+        };
+        Ok(xml_elements(&xml, "response").into_iter().filter_map(|res| {
+            let href = xml_elements(res, "href").into_iter().next()?;
+            let etag = xml_elements(res, "getetag").into_iter().next()?;
+            let data = xml_elements(res, "calendar-data").into_iter().next()
+                .filter(|data| !data.trim().is_empty())?;
+            Some(Remote {
+                href: self.to_path(&xml_unescape(href)),
+                etag: xml_unescape(etag),
+                data: xml_unescape(data),
+            })
+        }).collect())
+    }
+    // upload an object, only if it is unchanged (etag) or new (no etag)
+    fn put(&self, path: &str, ical: &str, etag: Option<&str>) -> Result<String, PutErr> {
+        let req = self.request("PUT", path)
+            .set("Content-Type", "text/calendar; charset=utf-8");
+        let req = match etag {
+            Some(etag) => req.set("If-Match", etag),
+            None => req.set("If-None-Match", "*"),
+        };
+        match req.send_string(ical) {
+            // servers may omit the etag, then the next listing looks like
+            // a remote change and the object is pulled back unchanged
+            Ok(res) => Ok(res.header("ETag").unwrap_or_default().to_string()),
+            Err(ureq::Error::Status(412, _)) => Err(PutErr::Precondition),
+            Err(ureq::Error::Status(code, _)) => Err(PutErr::Other(SyncErr::Status(code, path.to_string()))),
+            Err(e) => Err(PutErr::Other(SyncErr::Transport(e.to_string()))),
+        }
+    }
+    fn delete(&self, path: &str, etag: &str) -> Result<(), PutErr> {
+        match self.request("DELETE", path).set("If-Match", etag).call() {
+            Ok(_) | Err(ureq::Error::Status(404, _)) => Ok(()),
+            Err(ureq::Error::Status(412, _)) => Err(PutErr::Precondition),
+            Err(ureq::Error::Status(code, _)) => Err(PutErr::Other(SyncErr::Status(code, path.to_string()))),
+            Err(e) => Err(PutErr::Other(SyncErr::Transport(e.to_string()))),
+        }
+    }
+    // pull remote changes and push local changes
+    // objects changed on both sides since the last synchronization are reported as conflicts
+    pub fn sync(&self, db: &mut DataBase, state: &mut SyncState) -> Result<SyncReport, SyncErr> {
+        if state.url != self.config.url {
+            *state = SyncState { url: self.config.url.clone(), href: HashMap::new() };
+        }
+        let mut report = SyncReport::default();
+        let remotes = self.list()?;
+        let listed = remotes.iter().map(|r| r.href.clone()).collect::<HashSet<_>>();
+        let conflict = |report: &mut SyncReport, name: &str, href: &str, reason: String| {
+            report.conflicts.push(Conflict { name: name.to_string(), href: href.to_string(), reason });
+        };
+        for remote in remotes {
+            let Some(parsed) = ical_parse(&remote.data, db.tz()) else {
+                conflict(&mut report, "", &remote.href, String::from("unreadable calendar data"));
+                continue
+            };
+            let Some(entry) = state.href.get(&remote.href).cloned() else {
+                // new on the server
+                match ev_apply(db, None, &parsed) {
+                    Ok(ev) => {
+                        let print = fingerprint(&db.ev_get_by_id(ev).unwrap());
+                        state.href.insert(remote.href, SyncEntry { uid: parsed.uid, ev, etag: remote.etag, print });
+                        report.pulled += 1;
+                    }
+                    Err(e) => conflict(&mut report, &parsed.name, &remote.href, e),
+                }
+                continue
+            };
+            let local = db.ev_get_by_id(entry.ev);
+            let remote_changed = entry.etag != remote.etag;
+            let local_changed = local.as_ref().map(fingerprint) != Some(entry.print.clone());
+            match (local, remote_changed, local_changed) {
+                (_, false, false) => {}
+                (None, false, true) => match self.delete(&remote.href, &entry.etag) {
+                    Ok(()) => {
+                        state.href.remove(&remote.href);
+                        report.removed_remote += 1;
+                    }
+                    Err(PutErr::Precondition) => conflict(&mut report, &parsed.name, &remote.href,
+                        String::from("deleted locally but changed on the server")),
+                    Err(PutErr::Other(e)) => Err(e)?,
+                },
+                (None, true, _) => conflict(&mut report, &parsed.name, &remote.href,
+                    String::from("deleted locally but changed on the server")),
+                (Some(ev), true, true) => conflict(&mut report, ev.name(), &remote.href,
+                    String::from("changed both locally and on the server")),
+                (Some(ev), true, false) => match ev_apply(db, Some(ev.id()), &parsed) {
+                    Ok(id) => {
+                        let print = fingerprint(&db.ev_get_by_id(id).unwrap());
+                        state.href.insert(remote.href, SyncEntry { etag: remote.etag, print, ..entry });
+                        report.pulled += 1;
+                    }
+                    Err(e) => conflict(&mut report, ev.name(), &remote.href, e),
+                },
+                (Some(ev), false, true) => {
+                    let parent = db.pj_get_by_id(ev.pp()).map(|pj| pj.name().to_string()).unwrap_or_default();
+                    match self.put(&remote.href, &ical_format(&ev, &entry.uid, &parent), Some(&entry.etag)) {
+                        Ok(etag) => {
+                            let print = fingerprint(&ev);
+                            state.href.insert(remote.href, SyncEntry { etag, print, ..entry });
+                            report.pushed += 1;
+                        }
+                        Err(PutErr::Precondition) => conflict(&mut report, ev.name(), &remote.href,
+                            String::from("changed both locally and on the server")),
+                        Err(PutErr::Other(e)) => Err(e)?,
+                    }
+                }
+            }
+        }
+        // objects known before but no longer listed were deleted on the server
+        let gone = state.href.keys().filter(|h| !listed.contains(*h)).cloned().collect::<Vec<_>>();
+        for href in gone {
+            let entry = state.href[&href].clone();
+            match db.ev_get_by_id(entry.ev) {
+                None => { state.href.remove(&href); }
+                Some(ev) if fingerprint(&ev) != entry.print => conflict(&mut report, ev.name(), &href,
+                    String::from("deleted on the server but changed locally")),
+                Some(ev) => match db.ev_delete(ev.id()) {
+                    Ok(()) => {
+                        state.href.remove(&href);
+                        report.removed_local += 1;
+                    }
+                    Err(e) => conflict(&mut report, ev.name(), &href, e.to_string()),
+                }
+            }
+        }
+        // local events that were never uploaded
+        let known = state.href.values().map(|e| e.ev).collect::<HashSet<_>>();
+        let mut fresh = db.ev_list().unwrap_or_default().into_iter()
+            .filter_map(|name| db.ev_get_by_name(&name))
+            .filter(|ev| !known.contains(&ev.id()))
+            .collect::<Vec<_>>();
+        fresh.sort_by_key(|ev| ev.id());
+        for ev in fresh {
+            let uid = format!("tman-{}-{}", ev.id(), ev.time());
+            let href = format!("{}{uid}.ics", self.path());
+            let parent = db.pj_get_by_id(ev.pp()).map(|pj| pj.name().to_string()).unwrap_or_default();
+            match self.put(&href, &ical_format(&ev, &uid, &parent), None) {
+                Ok(etag) => {
+                    state.href.insert(href, SyncEntry { uid, ev: ev.id(), etag, print: fingerprint(&ev) });
+                    report.pushed += 1;
+                }
+                Err(PutErr::Precondition) => conflict(&mut report, ev.name(), &href,
+                    String::from("an object with the same uid already exists on the server")),
+                Err(PutErr::Other(e)) => Err(e)?,
+            }
+        }
+        Ok(report)
+    }
+}
+
+// everything of an event that is synchronized
+fn fingerprint(ev: &Ev) -> String {
+    format!("{}\n{}\n{}\n{}", ev.name(), ev.time(), ev.quota_esti(), ev.pp())
+}
+
+// create (id is none) or update a local event from a remote one
+fn ev_apply(db: &mut DataBase, id: Option<usize>, remote: &RemoteEv) -> Result<usize, String> {
+    let pp = remote.parent.as_ref()
+        .and_then(|name| db.pj_get_by_name(name))
+        .map(|pj| pj.id()).unwrap_or(0);
+    let Some(id) = id else {
+        let ev = Ev::new(remote.name.clone(), pp, remote.time, remote.quota);
+        return db.ev_create(ev).map_err(|e| format!("cannot create local event: {e}"))
+    };
+    let ev = db.ev_get_by_id(id).unwrap();
+    let err = |e: DBErr| format!("cannot update local event: {e}");
+    if ev.name() != remote.name { db.ev_set_name(id, remote.name.clone()).map_err(err)?; }
+    if ev.time() != remote.time { db.ev_set_time(id, remote.time).map_err(err)?; }
+    if ev.quota_esti() != remote.quota { db.ev_set_quota_esti(id, remote.quota).map_err(err)?; }
+    if ev.pp() != pp && remote.parent.is_some() { db.ev_set_pp(id, pp).map_err(err)?; }
+    Ok(id)
+}
+
+fn ical_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+fn ical_unescape(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' { out.push(c); continue }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+// fold content lines longer than 75 octets
+fn ical_fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out + "\r\n"
+}
+
+fn ical_time(ts: i64) -> String {
+    chrono::NaiveDateTime::from_timestamp_opt(ts, 0).unwrap()
+        .format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn ical_format(ev: &Ev, uid: &str, parent: &str) -> String {
+    let lines = [
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//tman//tman//EN"),
+        String::from("BEGIN:VEVENT"),
+        format!("UID:{uid}"),
+        format!("DTSTAMP:{}", ical_time(chrono::Utc::now().timestamp())),
+        format!("DTSTART:{}", ical_time(ev.time())),
+        format!("DTEND:{}", ical_time(ev.time() + ev.quota_esti() as i64 * QUOTA_UNIT)),
+        format!("SUMMARY:{}", ical_escape(ev.name())),
+        format!("X-TMAN-PARENT:{}", ical_escape(parent)),
+        format!("X-TMAN-COLOR:#{:06x}", ev.color_usize()),
+        String::from("END:VEVENT"),
+        String::from("END:VCALENDAR"),
+    ];
+    lines.iter().map(|l| ical_fold(l)).collect()
+}
+
+// read the first VEVENT of a calendar object
+fn ical_parse(data: &str, tz: i32) -> Option<RemoteEv> {
+    // unfold continuation lines
+    let data = data.replace("\r\n", "\n").replace("\n ", "").replace("\n\t", "");
+    let mut props = HashMap::new();
+    let mut inside = false;
+    for line in data.lines() {
+        match line {
+            "BEGIN:VEVENT" => { inside = true; continue }
+            "END:VEVENT" => break,
+            _ if !inside => continue,
+            _ => {}
+        }
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let name = key.split(';').next().unwrap().to_ascii_uppercase();
+        props.entry(name).or_insert(value.to_string());
+    }
+    let time = super::parse_time(props.get("DTSTART")?, tz)?;
+    let end = match (props.get("DTEND"), props.get("DURATION")) {
+        (Some(end), _) => super::parse_time(end, tz)?,
+        (None, Some(duration)) => time + super::parse_duration(duration)?,
+        (None, None) => time,
+    };
+    Some(RemoteEv {
+        uid: props.get("UID")?.clone(),
+        name: ical_unescape(props.get("SUMMARY").map(|s| s.as_str()).unwrap_or("untitled")),
+        quota: super::quota_from_secs(end - time),
+        parent: props.get("X-TMAN-PARENT").map(|s| ical_unescape(s)).filter(|s| !s.is_empty()),
+        time,
+    })
+}
+
+// inner text of all elements with a given local name, ignoring namespace prefixes
+fn xml_elements<'a>(xml: &'a str, local: &str) -> Vec<&'a str> {
+    let mut out = vec![];
+    let mut rest = xml;
+    while let Some(i) = rest.find('<') {
+        rest = &rest[i + 1..];
+        let end = rest.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(rest.len());
+        let qname = &rest[..end];
+        if qname.rsplit(':').next() != Some(local) { continue }
+        let Some(open) = rest.find('>') else { break };
+        if rest[..open].ends_with('/') { out.push(""); continue }
+        let inner = &rest[open + 1..];
+        let Some(close) = inner.find(&format!("</{qname}>")) else { break };
+        out.push(&inner[..close]);
+        rest = &inner[close..];
+    }
+    out
+}
+
+fn xml_unescape(s: &str) -> String {
+    let s = s.trim();
+    if let Some(s) = s.strip_prefix("<![CDATA[").and_then(|s| s.strip_suffix("]]>")) {
+        return s.to_string()
+    }
+    s.replace("&lt;", "<").replace("&gt;", ">")
+        .replace("&quot;", "\"").replace("&apos;", "'")
+        .replace("&#13;", "\r").replace("&#xD;", "\r").replace("&#xd;", "\r")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::sync::{Arc, Mutex};
+
+    // objects of the stand-in collection, path -> (etag, calendar data)
+    type Store = Arc<Mutex<HashMap<String, (String, String)>>>;
+
+    // a caldav server good enough for the client: REPORT lists the collection,
+    // PUT and DELETE honor If-Match and If-None-Match
+    fn serve() -> (String, Store) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/cal/", listener.local_addr().unwrap());
+        let store = Store::default();
+        let objects = store.clone();
+        std::thread::spawn(move || {
+            let mut etags = 0;
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut words = line.split_whitespace();
+                let (method, path) = (words.next().unwrap_or("").to_string(), words.next().unwrap_or("").to_string());
+                let mut headers = HashMap::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let Some((key, value)) = line.trim_end().split_once(':') else { break };
+                    headers.insert(key.to_ascii_lowercase(), value.trim().to_string());
+                }
+                let len = headers.get("content-length").and_then(|l| l.parse().ok()).unwrap_or(0);
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).unwrap();
+                let mut objects = objects.lock().unwrap();
+                let current = objects.get(&path).map(|(etag, _)| etag.clone());
+                let stale = match (headers.get("if-match"), headers.get("if-none-match")) {
+                    (Some(etag), _) => current.as_ref() != Some(etag),
+                    (None, Some(_)) => current.is_some(),
+                    (None, None) => false,
+                };
+                let (status, etag, reply) = match method.as_str() {
+                    "REPORT" => {
+                        let responses = objects.iter().map(|(href, (etag, data))| format!(
+                            "<d:response><d:href>{href}</d:href><d:propstat><d:prop><d:getetag>{etag}</d:getetag>\
+                            <c:calendar-data>{}</c:calendar-data></d:prop></d:propstat></d:response>",
+                            data.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"))).collect::<String>();
+                        ("207 Multi-Status", None, format!(
+                            "<?xml version=\"1.0\"?><d:multistatus xmlns:d=\"DAV:\" \
+                            xmlns:c=\"urn:ietf:params:xml:ns:caldav\">{responses}</d:multistatus>"))
+                    }
+                    _ if stale => ("412 Precondition Failed", None, String::new()),
+                    "PUT" => {
+                        etags += 1;
+                        let etag = format!("\"{etags}\"");
+                        objects.insert(path, (etag.clone(), String::from_utf8(body).unwrap()));
+                        ("201 Created", Some(etag), String::new())
+                    }
+                    "DELETE" if current.is_none() => ("404 Not Found", None, String::new()),
+                    "DELETE" => {
+                        objects.remove(&path);
+                        ("204 No Content", None, String::new())
+                    }
+                    _ => ("405 Method Not Allowed", None, String::new()),
+                };
+                let etag = etag.map(|e| format!("ETag: {e}\r\n")).unwrap_or_default();
+                write!(stream, "HTTP/1.1 {status}\r\n{etag}Content-Length: {}\r\nConnection: close\r\n\r\n{reply}", reply.len()).unwrap();
+            }
+        });
+        (url, store)
+    }
+
+    fn client(url: &str) -> CalDavClient {
+        CalDavClient::new(CalDavConfig { url: url.to_string(), user: String::from("me"), password: None })
+    }
+
+    // put an object on the server behind the client's back
+    fn remote_put(store: &Store, href: &str, etag: &str, uid: &str, summary: &str, start: &str, end: &str) {
+        let data = format!("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:{uid}\r\nDTSTART:{start}\r\nDTEND:{end}\r\n\
+            SUMMARY:{summary}\r\nX-TMAN-PARENT:work\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n");
+        store.lock().unwrap().insert(href.to_string(), (etag.to_string(), data));
+    }
+
+    fn summary(report: &SyncReport) -> (usize, usize, usize, usize, usize) {
+        (report.pulled, report.pushed, report.removed_local, report.removed_remote, report.conflicts.len())
+    }
+
+    #[test]
+    fn push_new_events_once() {
+        let (url, store) = serve();
+        let mut db = DataBase::new(0);
+        db.ev_create(Ev::new(String::from("standup"), 0, 1_790_000_000, 1)).unwrap();
+        let (client, mut state) = (client(&url), SyncState::default());
+        assert_eq!(summary(&client.sync(&mut db, &mut state).unwrap()), (0, 1, 0, 0, 0));
+        let objects = store.lock().unwrap().clone();
+        assert_eq!(objects.len(), 1);
+        let (href, (_, data)) = objects.iter().next().unwrap();
+        assert!(href.starts_with("/cal/tman-"));
+        assert!(data.contains("SUMMARY:standup"));
+        assert_eq!(summary(&client.sync(&mut db, &mut state).unwrap()), (0, 0, 0, 0, 0));
+    }
+
+    #[test]
+    fn pull_new_and_changed_objects() {
+        let (url, store) = serve();
+        let mut db = DataBase::new(0);
+        db.pj_create("work", 0).unwrap();
+        remote_put(&store, "/cal/a.ics", "\"a1\"", "a", "review", "20261020T100000Z", "20261020T120000Z");
+        let (client, mut state) = (client(&url), SyncState::default());
+        assert_eq!(summary(&client.sync(&mut db, &mut state).unwrap()), (1, 0, 0, 0, 0));
+        let ev = db.ev_get_by_name("review").unwrap();
+        assert_eq!((ev.quota_esti(), db.pj_get_by_id(ev.pp()).unwrap().name()), (2, "work"));
+        // a new etag is a change on the server
+        remote_put(&store, "/cal/a.ics", "\"a2\"", "a", "code review", "20261020T100000Z", "20261020T130000Z");
+        assert_eq!(summary(&client.sync(&mut db, &mut state).unwrap()), (1, 0, 0, 0, 0));
+        let ev = db.ev_get_by_id(ev.id()).unwrap();
+        assert_eq!((ev.name(), ev.quota_esti()), ("code review", 3));
+    }
+
+    #[test]
+    fn push_local_changes_with_the_etag() {
+        let (url, store) = serve();
+        let mut db = DataBase::new(0);
+        remote_put(&store, "/cal/a.ics", "\"a1\"", "a", "review", "20261020T100000Z", "20261020T120000Z");
+        let (client, mut state) = (client(&url), SyncState::default());
+        client.sync(&mut db, &mut state).unwrap();
+        let id = db.ev_get_by_name("review").unwrap().id();
+        db.ev_set_quota_esti(id, 4).unwrap();
+        assert_eq!(summary(&client.sync(&mut db, &mut state).unwrap()), (0, 1, 0, 0, 0));
+        let (etag, data) = store.lock().unwrap()["/cal/a.ics"].clone();
+        assert_ne!(etag, "\"a1\"");
+        assert!(data.contains("DTEND:20261020T140000Z"));
+    }
+
+    #[test]
+    fn changes_on_both_sides_conflict() {
+        let (url, store) = serve();
+        let mut db = DataBase::new(0);
+        remote_put(&store, "/cal/a.ics", "\"a1\"", "a", "review", "20261020T100000Z", "20261020T120000Z");
+        let (client, mut state) = (client(&url), SyncState::default());
+        client.sync(&mut db, &mut state).unwrap();
+        let id = db.ev_get_by_name("review").unwrap().id();
+        db.ev_set_name(id, String::from("local review")).unwrap();
+        remote_put(&store, "/cal/a.ics", "\"a2\"", "a", "remote review", "20261020T100000Z", "20261020T120000Z");
+        let report = client.sync(&mut db, &mut state).unwrap();
+        assert_eq!(summary(&report), (0, 0, 0, 0, 1));
+        assert_eq!(report.conflicts[0].href, "/cal/a.ics");
+        // both sides are left as they are
+        assert_eq!(db.ev_get_by_id(id).unwrap().name(), "local review");
+        assert!(store.lock().unwrap()["/cal/a.ics"].1.contains("SUMMARY:remote review"));
+    }
+
+    #[test]
+    fn deletes_go_both_ways() {
+        let (url, store) = serve();
+        let mut db = DataBase::new(0);
+        remote_put(&store, "/cal/a.ics", "\"a1\"", "a", "review", "20261020T100000Z", "20261020T120000Z");
+        remote_put(&store, "/cal/b.ics", "\"b1\"", "b", "retro", "20261021T100000Z", "20261021T110000Z");
+        let (client, mut state) = (client(&url), SyncState::default());
+        assert_eq!(summary(&client.sync(&mut db, &mut state).unwrap()), (2, 0, 0, 0, 0));
+        // gone from the server, then gone locally
+        store.lock().unwrap().remove("/cal/a.ics");
+        let id = db.ev_get_by_name("retro").unwrap().id();
+        db.ev_delete(id).unwrap();
+        assert_eq!(summary(&client.sync(&mut db, &mut state).unwrap()), (0, 0, 1, 1, 0));
+        assert!(db.ev_get_by_name("review").is_none());
+        assert!(store.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn ical_round_trip() {
+        let mut db = DataBase::new(0);
+        let pp = db.pj_create("home, sweet", 0).unwrap();
+        let name = format!("pack; the {} books", "very ".repeat(20));
+        let id = db.ev_create(Ev::new(name.clone(), pp, 1_790_000_000, 3)).unwrap();
+        let ical = ical_format(&db.ev_get_by_id(id).unwrap(), "uid-1", "home, sweet");
+        assert!(ical.lines().all(|l| l.trim_end_matches('\r').len() <= 75));
+        let ev = ical_parse(&ical, 0).unwrap();
+        assert_eq!((ev.uid.as_str(), ev.name.as_str(), ev.time, ev.quota), ("uid-1", name.as_str(), 1_790_000_000, 3));
+        assert_eq!(ev.parent.as_deref(), Some("home, sweet"));
+    }
+
+    #[test]
+    fn ical_fold_splits_on_characters() {
+        let line = "é".repeat(50);
+        let folded = ical_fold(&line);
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", "").trim_end(), line);
+    }
+
+    #[test]
+    fn ical_parse_duration_and_defaults() {
+        let data = "BEGIN:VEVENT\nUID:x\nDTSTART:20261020T100000Z\nDURATION:PT90M\nEND:VEVENT\n";
+        let ev = ical_parse(data, 0).unwrap();
+        assert_eq!((ev.name.as_str(), ev.quota, ev.parent), ("untitled", 2, None));
+        assert!(ical_parse("BEGIN:VEVENT\nUID:x\nEND:VEVENT\n", 0).is_none());
+    }
+
+    #[test]
+    fn xml_elements_ignore_prefixes() {
+        let xml = "<D:multistatus><D:response><D:href>/a%20b.ics</D:href><D:getetag/></D:response>\
+            <response><href>/c.ics</href><getetag>&quot;1&quot;</getetag></response></D:multistatus>";
+        assert_eq!(xml_elements(xml, "href"), vec!["/a%20b.ics", "/c.ics"]);
+        assert_eq!(xml_elements(xml, "getetag"), vec!["", "&quot;1&quot;"]);
+        assert_eq!(xml_unescape(" <![CDATA[a<b]]> "), "a<b");
+        assert_eq!(xml_unescape("&amp;lt;"), "&lt;");
+    }
+}
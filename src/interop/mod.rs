@@ -0,0 +1,90 @@
+//! exchange of projects and events with other tools
+pub mod caldav;
+pub mod org;
+pub mod report;
+pub mod taskwarrior;
+pub mod todotxt;
+
+use crate::data::*;
+
+// outcome of an import, rejected items do not abort the import
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub rejected: Vec<(String, String)>,
+}
+
+impl ImportReport {
+    pub fn reject(&mut self, item: &str, reason: impl Into<String>) {
+        self.rejected.push((item.to_string(), reason.into()));
+    }
+    pub fn summary(&self) -> String {
+        let mut s = format!("created {}, updated {}, rejected {}",
+            self.created, self.updated, self.rejected.len());
+        for (item, reason) in self.rejected.iter() {
+            s.push_str(&format!("; {item}: {reason}"));
+        }
+        s
+    }
+}
+
+// find or create a project under a given parent
+// an existing project with the same name elsewhere in the tree is a name clash
+pub(crate) fn project_under(db: &mut DataBase, name: &str, pp: usize, report: &mut ImportReport) -> Option<usize> {
+    match db.pj_get_by_name(name) {
+        Some(pj) if pj.pp() == pp => Some(pj.id()),
+        Some(_) => { report.reject(name, PjErr::NameNotDistinct.to_string()); None }
+        None => match db.pj_create(name, pp) {
+            Ok(id) => { report.created += 1; Some(id) }
+            Err(e) => { report.reject(name, e.to_string()); None }
+        }
+    }
+}
+
+// find or create the projects along a dotted path like work.docs
+pub(crate) fn project_path(db: &mut DataBase, path: &str, report: &mut ImportReport) -> Option<usize> {
+    path.split('.').filter(|s| !s.is_empty())
+        .try_fold(0, |pp, name| project_under(db, name, pp, report))
+}
+
+// round a length in seconds up to whole quota units
+pub(crate) fn quota_from_secs(secs: i64) -> usize {
+    (secs.max(0) + QUOTA_UNIT - 1).div_euclid(QUOTA_UNIT) as usize
+}
+
+// parse a date or date-time value into utc seconds
+// floating and TZID times are taken as database local time
+pub(crate) fn parse_time(value: &str, tz: i32) -> Option<i64> {
+    use chrono::{NaiveDate, NaiveDateTime};
+    let local = |t: NaiveDateTime| t.timestamp() - tz as i64 * 60 * 60;
+    if let Some(value) = value.strip_suffix('Z') {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok().map(|t| t.timestamp())
+    } else if value.contains('T') {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok().map(local)
+    } else {
+        NaiveDate::parse_from_str(value, "%Y%m%d").ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0)).map(local)
+    }
+}
+
+// parse a duration like P1DT2H30M into seconds
+pub(crate) fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim_start_matches(['+', '-']).strip_prefix('P')?;
+    let (mut secs, mut num, mut time) = (0i64, 0i64, false);
+    for c in value.chars() {
+        match c {
+            '0'..='9' => num = num * 10 + c.to_digit(10)? as i64,
+            'Y' => { secs += num * 365 * 24 * 60 * 60; num = 0 }
+            'M' if !time => { secs += num * 30 * 24 * 60 * 60; num = 0 }
+            'W' => { secs += num * 7 * 24 * 60 * 60; num = 0 }
+            'D' => { secs += num * 24 * 60 * 60; num = 0 }
+            'H' => { secs += num * 60 * 60; num = 0 }
+            'M' => { secs += num * 60; num = 0 }
+            'S' => { secs += num; num = 0 }
+            'T' => time = true,
+            _ => None?,
+        }
+    }
+    Some(secs)
+}
@@ -0,0 +1,279 @@
+//! emacs org-mode export and import of the project tree
+use std::collections::{HashMap, HashSet};
+use crate::data::*;
+use super::*;
+
+fn org_time(ts: i64, tz: i32) -> String {
+    chrono::NaiveDateTime::from_timestamp_opt(ts + tz as i64 * 60 * 60, 0).unwrap()
+        .format("<%Y-%m-%d %a %H:%M>").to_string()
+}
+
+// parse <2026-11-01 Sun 17:00>, the weekday and time are optional
+fn org_parse_time(s: &str, tz: i32) -> Option<i64> {
+    use chrono::{NaiveDate, NaiveTime};
+    let s = s.trim().trim_start_matches(['<', '[']).trim_end_matches(['>', ']']);
+    let mut words = s.split_whitespace();
+    let date = NaiveDate::parse_from_str(words.next()?, "%Y-%m-%d").ok()?;
+    let time = words.find(|w| w.contains(':'))
+        .map(|w| NaiveTime::parse_from_str(w.split('-').next().unwrap(), "%H:%M"))
+        .unwrap_or(Ok(NaiveTime::MIN)).ok()?;
+    Some(date.and_time(time).timestamp() - tz as i64 * 60 * 60)
+}
+
+fn weight_tag(weight_type: WeightType) -> &'static str {
+    match weight_type {
+        WeightType::Flexible => "flexible",
+        WeightType::Reserved => "reserved",
+    }
+}
+
+fn export_pj(db: &DataBase, id: usize, depth: usize, out: &mut String) {
+    let tz = db.tz();
+    let pj = db.pj_get_by_id(id).unwrap();
+    if id != 0 {
+        out.push_str(&format!("{} {} :{}:\n", "*".repeat(depth), pj.name(), weight_tag(pj.weight_type())));
+        if let Some(due) = pj.deadline() {
+            out.push_str(&format!("DEADLINE: {}\n", org_time(due, tz)));
+        }
+        let deps = pj.iter_deps()
+            .map(|id| db.pj_get_by_id(id).unwrap().name().to_string())
+            .collect::<Vec<_>>();
+        out.push_str(":PROPERTIES:\n");
+        out.push_str(&format!(":ID: p{}\n", pj.id()));
+        out.push_str(&format!(":QUOTA_DONE: {}\n", pj.quota_done()));
+        out.push_str(&format!(":QUOTA_ESTI: {}\n", pj.quota_esti()));
+        out.push_str(&format!(":WEIGHT: {}\n", pj.weight()));
+        out.push_str(&format!(":COLOR: #{:06x}\n", pj.color_usize()));
+        if !deps.is_empty() { out.push_str(&format!(":DEPS: {}\n", deps.join(", "))) }
+        out.push_str(":END:\n");
+    }
+    let mut evs = pj.iter_chev().map(|id| db.ev_get_by_id(id).unwrap()).collect::<Vec<_>>();
+    evs.sort_by_key(|ev| ev.time());
+    for ev in evs {
+        out.push_str(&format!("{} {}\n", "*".repeat(depth + 1), ev.name()));
+        out.push_str(&format!("SCHEDULED: {}\n", org_time(ev.time(), tz)));
+        out.push_str(&format!(":PROPERTIES:\n:ID: e{}\n:QUOTA_ESTI: {}\n:END:\n", ev.id(), ev.quota_esti()));
+    }
+    let mut chpj = pj.iter_chpj().collect::<Vec<_>>();
+    chpj.sort();
+    for ch in chpj {
+        export_pj(db, ch, depth + 1, out);
+    }
+}
+
+// render the whole project tree, children of root are top level headings
+pub fn export(db: &DataBase) -> String {
+    let mut out = String::from("#+TITLE: tman\n");
+    export_pj(db, 0, 0, &mut out);
+    out
+}
+
+// a heading read from an org file
+#[derive(Debug, Default)]
+struct Node {
+    level: usize,
+    name: String,
+    tags: Vec<String>,
+    deadline: Option<String>,
+    scheduled: Option<String>,
+    props: HashMap<String, String>,
+    parent: Option<usize>,
+}
+
+fn parse(text: &str) -> Vec<Node> {
+    let mut nodes: Vec<Node> = vec![];
+    let mut drawer = false;
+    for line in text.lines() {
+        let stars = line.chars().take_while(|c| *c == '*').count();
+        if stars > 0 && line[stars..].starts_with(' ') {
+            let mut title = line[stars..].trim();
+            let mut tags = vec![];
+            if let Some((head, tail)) = title.rsplit_once(' ') {
+                if tail.len() > 2 && tail.starts_with(':') && tail.ends_with(':') {
+                    tags = tail.trim_matches(':').split(':').map(String::from).collect();
+                    title = head.trim_end();
+                }
+            }
+            let parent = nodes.iter().rposition(|n| n.level < stars);
+            nodes.push(Node { level: stars, name: title.to_string(), tags, parent, ..Default::default() });
+            drawer = false;
+            continue
+        }
+        let Some(node) = nodes.last_mut() else { continue };
+        let line = line.trim();
+        match line {
+            ":PROPERTIES:" => drawer = true,
+            ":END:" => drawer = false,
+            _ if drawer => if let Some((key, value)) = line.trim_start_matches(':').split_once(':') {
+                node.props.insert(key.to_ascii_uppercase(), value.trim().to_string());
+            },
+            _ => for (key, value) in [("DEADLINE:", &mut node.deadline), ("SCHEDULED:", &mut node.scheduled)] {
+                if let Some(i) = line.find(key) {
+                    let rest = &line[i + key.len()..];
+                    let end = rest.find(['>', ']']).map(|e| e + 1).unwrap_or(rest.len());
+                    *value = Some(rest[..end].trim().to_string());
+                }
+            }
+        }
+    }
+    nodes
+}
+
+fn parse_color(s: &str) -> Option<usize> {
+    usize::from_str_radix(s.trim_start_matches('#'), 16).ok().filter(|c| *c < 1 << 24)
+}
+
+// remove the dependencies of a project on both sides
+fn rmv_all_deps(db: &mut DataBase, id: usize) -> Result<(), DBErr> {
+    let Some(pj) = db.pj_get_by_id(id) else { return Ok(()) };
+    let deps = pj.iter_deps().map(|dep| (id, dep));
+    let rvs = pj.iter_deps_rvs().map(|dep| (dep, id));
+    for (id, dep) in deps.chain(rvs).collect::<Vec<_>>() {
+        db.pj_rmv_deps(id, dep)?;
+    }
+    Ok(())
+}
+
+// apply the differences between an org file and the database as one undoable step
+// headings with SCHEDULED are events, the others are projects
+// projects and events missing from the file are deleted
+pub fn import(db: &mut DataBase, text: &str) -> ImportReport {
+    db.transaction(|db| {
+        let mut report = ImportReport::default();
+        let nodes = parse(text);
+        let tz = db.tz();
+        // node index -> project id, only for project nodes
+        let mut pjs = HashMap::<usize, usize>::new();
+        let mut seen_pj = HashSet::from([0usize]);
+        let mut seen_ev = HashSet::new();
+        for (i, node) in nodes.iter().enumerate() {
+            let pp = match node.parent {
+                None => 0,
+                Some(p) => match pjs.get(&p) {
+                    Some(&pp) => pp,
+                    None => { report.reject(&node.name, "its parent project was not imported"); continue }
+                }
+            };
+            let id = node.props.get("ID").and_then(|id| id.get(1..)?.parse::<usize>().ok());
+            let err = |e: DBErr| e.to_string();
+            if let Some(time) = &node.scheduled {
+                // event
+                let Some(time) = org_parse_time(time, tz) else {
+                    report.reject(&node.name, "the timestamp is not valid"); continue
+                };
+                let quota = node.props.get("QUOTA_ESTI").and_then(|q| q.parse().ok()).unwrap_or(0);
+                let ev = id.filter(|_| node.props["ID"].starts_with('e'))
+                    .and_then(|id| db.ev_get_by_id(id))
+                    .or_else(|| db.ev_get_by_name(&node.name));
+                let res = match ev {
+                    None => db.ev_create(Ev::new(node.name.clone(), pp, time, quota))
+                        .inspect(|_| report.created += 1),
+                    Some(ev) => (|| {
+                        let id = ev.id();
+                        if ev.name() != node.name { db.ev_set_name(id, node.name.clone())? }
+                        if ev.time() != time { db.ev_set_time(id, time)? }
+                        if ev.quota_esti() != quota { db.ev_set_quota_esti(id, quota)? }
+                        if ev.pp() != pp { db.ev_set_pp(id, pp)? }
+                        report.updated += 1;
+                        Ok(id)
+                    })(),
+                };
+                match res {
+                    Ok(id) => { seen_ev.insert(id); }
+                    Err(e) => report.reject(&node.name, err(e)),
+                }
+                continue
+            }
+            // project
+            let pj = id.filter(|_| node.props["ID"].starts_with('p'))
+                .and_then(|id| db.pj_get_by_id(id))
+                .or_else(|| db.pj_get_by_name(&node.name));
+            let id = match pj {
+                None => match db.pj_create(&node.name, pp) {
+                    Ok(id) => { report.created += 1; id }
+                    Err(e) => { report.reject(&node.name, err(e)); continue }
+                },
+                Some(pj) => {
+                    let res = (|| {
+                        if pj.name() != node.name { db.pj_set_name(pj.id(), node.name.clone())? }
+                        // only projects without dependencies move, the ones in the file are added back below
+                        if pj.pp() != pp {
+                            rmv_all_deps(db, pj.id())?;
+                            db.pj_set_pp(pj.id(), pp)?
+                        }
+                        Ok(())
+                    })();
+                    match res {
+                        Ok(()) => report.updated += 1,
+                        Err(e) => report.reject(&node.name, err(e)),
+                    }
+                    pj.id()
+                }
+            };
+            pjs.insert(i, id);
+            seen_pj.insert(id);
+            let pj = db.pj_get_by_id(id).unwrap();
+            let deadline = node.deadline.as_ref().and_then(|t| org_parse_time(t, tz));
+            let prop = |key: &str| node.props.get(key).and_then(|v| v.parse::<usize>().ok());
+            let weight_type = if node.tags.iter().any(|t| t == "flexible") { WeightType::Flexible }
+                else if node.tags.iter().any(|t| t == "reserved") { WeightType::Reserved }
+                else { pj.weight_type() };
+            let res = (|| {
+                if pj.deadline() != deadline { db.pj_set_deadline(id, deadline)? }
+                if let Some(esti) = prop("QUOTA_ESTI").filter(|q| *q != pj.quota_esti()) { db.pj_set_quota_esti(id, esti)? }
+                if let Some(done) = prop("QUOTA_DONE").filter(|q| *q != pj.quota_done()) { db.pj_set_quota_done(id, done)? }
+                if let Some(weight) = prop("WEIGHT").filter(|w| *w != pj.weight()) { db.pj_set_weight(id, weight)? }
+                if pj.weight_type() != weight_type { db.pj_set_weight_type(id, weight_type)? }
+                if let Some(c) = node.props.get("COLOR").and_then(|c| parse_color(c)).filter(|c| *c != pj.color_usize()) {
+                    db.pj_set_color(id, ((c >> 16) as u8, (c >> 8 & 0xff) as u8, (c & 0xff) as u8))?
+                }
+                Ok(())
+            })();
+            if let Err(e) = res { report.reject(&node.name, err(e)) }
+        }
+        // events and projects that are no longer in the file
+        for name in db.ev_list().unwrap_or_default() {
+            let ev = db.ev_get_by_name(&name).unwrap();
+            if seen_ev.contains(&ev.id()) { continue }
+            match db.ev_delete(ev.id()) {
+                Ok(()) => report.updated += 1,
+                Err(e) => report.reject(&name, e.to_string()),
+            }
+        }
+        let mut gone = db.pj_list().unwrap_or_default().into_iter()
+            .filter_map(|name| db.pj_get_by_name(&name))
+            .filter(|pj| !seen_pj.contains(&pj.id()))
+            .collect::<Vec<_>>();
+        // projects are read again, an earlier one may have taken an edge away already
+        for pj in gone.iter() {
+            if let Err(e) = rmv_all_deps(db, pj.id()) { report.reject(pj.name(), e.to_string()) }
+        }
+        // children go before their parents
+        gone.sort_by_key(|pj| std::cmp::Reverse(pj.id()));
+        while !gone.is_empty() {
+            let len = gone.len();
+            gone.retain(|pj| db.pj_delete(pj.id()).is_err());
+            if gone.len() == len { break }
+            report.updated += len - gone.len();
+        }
+        for pj in gone { report.reject(pj.name(), PjErr::BannedByChildren.to_string()) }
+        // dependencies are compared once every project is in place
+        for (i, node) in nodes.iter().enumerate() {
+            let Some(&id) = pjs.get(&i) else { continue };
+            let want = node.props.get("DEPS").map(|d| d.split(',')
+                .map(|s| s.trim()).filter(|s| !s.is_empty())
+                .filter_map(|name| match db.pj_get_by_name(name) {
+                    Some(pj) => Some(pj.id()),
+                    None => { report.reject(&node.name, "a project it depends on was not imported"); None }
+                }).collect::<HashSet<_>>()).unwrap_or_default();
+            let have = db.pj_get_by_id(id).unwrap().iter_deps().collect::<HashSet<_>>();
+            for &dep in have.difference(&want) {
+                if let Err(e) = db.pj_rmv_deps(id, dep) { report.reject(&node.name, e.to_string()) }
+            }
+            for &dep in want.difference(&have) {
+                if let Err(DBErr::Pj(e)) = db.pj_add_deps(id, dep) { report.reject(&node.name, e.to_string()) }
+            }
+        }
+        report
+    })
+}
@@ -0,0 +1,144 @@
+//! markdown and csv progress reports
+use std::collections::HashSet;
+use crate::data::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Csv,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md" | "markdown" => Ok(Format::Markdown),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown report format {s}, use md or csv")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportOpt {
+    pub format: Format,
+    // name of the subtree root
+    pub root: String,
+    // levels shown below the root, unlimited if none
+    pub depth: Option<usize>,
+    // event window in utc seconds, [from, to)
+    pub from: i64,
+    pub to: i64,
+}
+
+impl ReportOpt {
+    // whole tree, events from a week ago to a week ahead
+    pub fn new(format: Format) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        let week = 7 * 24 * 60 * 60;
+        ReportOpt { format, root: String::from("root"), depth: None, from: now - week, to: now + week }
+    }
+    // parse the start of a local day, YYYY/MM/DD
+    pub fn parse_date(s: &str, db: &DataBase) -> Result<i64, String> {
+        let date = chrono::NaiveDate::parse_from_str(s, "%Y/%m/%d")
+            .map_err(|_| format!("invalid date {s}, use YYYY/MM/DD"))?;
+        Ok(date.and_hms_opt(0, 0, 0).unwrap().timestamp() - db.tz() as i64 * 60 * 60)
+    }
+    // options of the report subcommand
+    // --format md|csv --root NAME --depth N --from YYYY/MM/DD --to YYYY/MM/DD
+    pub fn from_args(args: &[String], db: &DataBase) -> Result<Self, String> {
+        let mut opt = Self::new(Format::Markdown);
+        let mut args = args.iter();
+        while let Some(key) = args.next() {
+            let value = args.next().ok_or(format!("missing value for {key}"))?;
+            match key.as_str() {
+                "--format" => opt.format = value.parse()?,
+                "--root" => opt.root = value.clone(),
+                "--depth" => opt.depth = Some(value.parse().map_err(|_| format!("invalid depth {value}"))?),
+                "--from" => opt.from = Self::parse_date(value, db)?,
+                // the end date is included
+                "--to" => opt.to = Self::parse_date(value, db)? + 24 * 60 * 60,
+                _ => Err(format!("unknown option {key}"))?,
+            }
+        }
+        Ok(opt)
+    }
+}
+
+// one line of the project tree
+struct Row {
+    level: usize,
+    pj: Pj,
+    overdue: bool,
+}
+
+fn collect(db: &DataBase, id: usize, level: usize, opt: &ReportOpt, now: i64, rows: &mut Vec<Row>, subtree: &mut HashSet<usize>) {
+    let pj = db.pj_get_by_id(id).unwrap();
+    subtree.insert(id);
+    let mut chpj = pj.iter_chpj().map(|id| db.pj_get_by_id(id).unwrap()).collect::<Vec<_>>();
+    chpj.sort_by(|a, b| a.deadline().unwrap_or(i64::MAX).cmp(&b.deadline().unwrap_or(i64::MAX)).then(a.name().cmp(b.name())));
+    // the implicit root is not a line of its own
+    if id != 0 && opt.depth.is_none_or(|d| level <= d) {
+        let finished = pj.quota_esti() > 0 && pj.quota_done() >= pj.quota_esti();
+        let overdue = pj.deadline().is_some_and(|due| due < now) && !finished;
+        rows.push(Row { level, pj, overdue });
+    }
+    for ch in chpj {
+        collect(db, ch.id(), level + 1, opt, now, rows, subtree);
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) { format!("\"{}\"", s.replace('"', "\"\"")) } else { s.to_string() }
+}
+
+pub fn render(db: &DataBase, opt: &ReportOpt) -> Result<String, String> {
+    let root = db.pj_get_by_name(&opt.root).ok_or(format!("project {} does not exist", opt.root))?;
+    let now = chrono::Utc::now().timestamp();
+    let tz = db.tz() as i64 * 60 * 60;
+    let time = |ts: i64| chrono::NaiveDateTime::from_timestamp_opt(ts + tz, 0).unwrap()
+        .format("%Y/%m/%d %H:%M").to_string();
+    let mut rows = vec![];
+    let mut subtree = HashSet::new();
+    collect(db, root.id(), 0, opt, now, &mut rows, &mut subtree);
+    let mut evs = db.ev_list().unwrap_or_default().into_iter()
+        .filter_map(|name| db.ev_get_by_name(&name))
+        .filter(|ev| subtree.contains(&ev.pp()) && opt.from <= ev.time() && ev.time() < opt.to)
+        .collect::<Vec<_>>();
+    evs.sort_by_key(|ev| ev.time());
+    let parent = |ev: &Ev| db.pj_get_by_id(ev.pp()).unwrap().name().to_string();
+    // children of the implicit root are the top level
+    let base = (root.id() == 0) as usize;
+    let mut out = String::new();
+    match opt.format {
+        Format::Markdown => {
+            out.push_str(&format!("# {}\n\n## Projects\n\n", root.name()));
+            for Row { level, pj, overdue } in rows.iter() {
+                out.push_str(&format!("{}- **{}** {}/{}", "  ".repeat(level - base), pj.name(), pj.quota_done(), pj.quota_esti()));
+                if let Some(due) = pj.deadline() { out.push_str(&format!(" due {}", time(due))) }
+                if *overdue { out.push_str(" **overdue**") }
+                out.push('\n');
+            }
+            out.push_str(&format!("\n## Events {} - {}\n\n", time(opt.from), time(opt.to)));
+            out.push_str("| time | event | project | quota |\n|---|---|---|---|\n");
+            for ev in evs.iter() {
+                let cell = |s: &str| s.replace('|', "\\|");
+                out.push_str(&format!("| {} | {} | {} | {} |\n", time(ev.time()), cell(ev.name()), cell(&parent(ev)), ev.quota_esti()));
+            }
+        }
+        Format::Csv => {
+            out.push_str("kind,name,parent,depth,quota_done,quota_esti,due,overdue,time\n");
+            for Row { level, pj, overdue } in rows.iter() {
+                let pp = db.pj_get_by_id(pj.pp()).unwrap();
+                out.push_str(&format!("project,{},{},{},{},{},{},{},\n",
+                    csv_field(pj.name()), csv_field(pp.name()), level - base, pj.quota_done(), pj.quota_esti(),
+                    pj.deadline().map(time).unwrap_or_default(), overdue));
+            }
+            for ev in evs.iter() {
+                out.push_str(&format!("event,{},{},,,{},,,{}\n",
+                    csv_field(ev.name()), csv_field(&parent(ev)), ev.quota_esti(), time(ev.time())));
+            }
+        }
+    }
+    Ok(out)
+}
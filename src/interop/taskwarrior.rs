@@ -0,0 +1,91 @@
+//! import of taskwarrior json exports
+use std::collections::HashMap;
+use serde_json::Value;
+use crate::data::*;
+use super::*;
+
+// estimate is read as a number of quota units or as an iso duration
+fn estimate(value: &Value) -> Option<usize> {
+    match value {
+        Value::Number(n) => Some(n.as_f64()?.max(0.0).ceil() as usize),
+        Value::String(s) => s.parse::<f64>().ok().map(|n| n.max(0.0).ceil() as usize)
+            .or_else(|| parse_duration(s).map(quota_from_secs)),
+        _ => None,
+    }
+}
+
+// depends is a comma separated string in older exports and an array in newer ones
+fn depends(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => s.split(',').map(|s| s.trim().to_string()).collect(),
+        Value::Array(a) => a.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect(),
+        _ => vec![],
+    }
+}
+
+// import the output of `task export`
+// every task becomes a project under its dotted project path, scheduled tasks also get an event
+pub fn import(db: &mut DataBase, json: &str) -> Result<ImportReport, String> {
+    let tasks = match serde_json::from_str::<Value>(json) {
+        Ok(Value::Array(tasks)) => tasks,
+        // older versions print one object per line
+        _ => json.lines()
+            .map(|l| l.trim().trim_end_matches(','))
+            .filter(|l| !l.is_empty() && *l != "[" && *l != "]")
+            .map(serde_json::from_str::<Value>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("not a taskwarrior export: {e}"))?,
+    };
+    let mut report = ImportReport::default();
+    let mut uuid = HashMap::new();
+    for task in tasks.iter() {
+        let get = |key: &str| task.get(key).and_then(|v| v.as_str());
+        if get("status") == Some("deleted") { continue }
+        let Some(name) = get("description") else {
+            report.reject(get("uuid").unwrap_or("?"), "there is no description");
+            continue
+        };
+        let pp = match get("project") {
+            None => 0,
+            Some(path) => match project_path(db, path, &mut report) {
+                Some(pp) => pp,
+                None => { report.reject(name, "its parent project was not imported"); continue }
+            }
+        };
+        let existed = db.pj_get_by_name(name).is_some();
+        let Some(id) = project_under(db, name, pp, &mut report) else { continue };
+        if existed { report.updated += 1 }
+        if let Some(due) = get("due").and_then(|t| parse_time(t, db.tz())) {
+            db.pj_set_deadline(id, Some(due)).unwrap();
+        }
+        if let Some(quota) = task.get("estimate").and_then(estimate) {
+            db.pj_set_quota_esti(id, quota).unwrap();
+        }
+        if get("status") == Some("completed") {
+            let quota = db.pj_get_by_id(id).unwrap().quota_esti().max(1);
+            db.pj_set_quota_done(id, quota).unwrap();
+        }
+        if let Some(time) = get("scheduled").and_then(|t| parse_time(t, db.tz())) {
+            let quota = db.pj_get_by_id(id).unwrap().quota_esti();
+            let res = match db.ev_get_by_name(name) {
+                Some(ev) if ev.time() == time => Ok(()),
+                Some(ev) => db.ev_set_time(ev.id(), time),
+                None => db.ev_create(Ev::new(name.to_string(), id, time, quota)).map(|_| ()),
+            };
+            if let Err(e) = res { report.reject(name, e.to_string()) }
+        }
+        if let Some(u) = get("uuid") { uuid.insert(u.to_string(), id); }
+    }
+    // dependencies are resolved after all tasks exist
+    for task in tasks.iter() {
+        let Some(&id) = task.get("uuid").and_then(|u| uuid.get(u.as_str()?)) else { continue };
+        let name = db.pj_get_by_id(id).unwrap().name().to_string();
+        for dep in task.get("depends").map(depends).unwrap_or_default() {
+            match uuid.get(&dep) {
+                None => report.reject(&name, "a task it depends on was not imported"),
+                Some(&dep) => if let Err(e) = db.pj_add_deps(id, dep) { report.reject(&name, e.to_string()) },
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,75 @@
+//! import of todo.txt files
+use crate::data::*;
+use super::*;
+
+// one parsed todo.txt line
+struct Task<'a> {
+    done: bool,
+    name: String,
+    project: Option<&'a str>,
+    due: Option<&'a str>,
+}
+
+fn is_date(s: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
+}
+
+fn parse_line(line: &str) -> Task<'_> {
+    let mut words = line.split_whitespace().peekable();
+    let done = words.next_if_eq(&"x").is_some();
+    // completion and creation dates, priority
+    if done { words.next_if(|w| is_date(w)); }
+    words.next_if(|w| w.len() == 3 && w.starts_with('(') && w.ends_with(')'));
+    words.next_if(|w| is_date(w));
+    let mut task = Task { done, name: String::new(), project: None, due: None };
+    let mut name = vec![];
+    for word in words {
+        if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+            task.project = task.project.or(Some(project));
+        } else if let Some(due) = word.strip_prefix("due:") {
+            task.due = Some(due);
+        } else if word.starts_with('@') && word.len() > 1 {
+            // contexts have no counterpart
+        } else if word.split_once(':').is_some_and(|(k, v)| !k.is_empty() && !v.is_empty() && !v.starts_with('/')) {
+            // other key:value extensions have no counterpart
+        } else {
+            name.push(word);
+        }
+    }
+    task.name = name.join(" ");
+    task
+}
+
+// import a todo.txt file, every line becomes a project under its first +project
+pub fn import(db: &mut DataBase, text: &str) -> ImportReport {
+    let mut report = ImportReport::default();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let task = parse_line(line);
+        if task.name.is_empty() {
+            report.reject(line, "there is no description");
+            continue
+        }
+        let pp = match task.project {
+            None => 0,
+            Some(path) => match project_path(db, path, &mut report) {
+                Some(pp) => pp,
+                None => { report.reject(&task.name, "its parent project was not imported"); continue }
+            }
+        };
+        let existed = db.pj_get_by_name(&task.name).is_some();
+        let Some(id) = project_under(db, &task.name, pp, &mut report) else { continue };
+        if existed { report.updated += 1 }
+        if let Some(due) = task.due {
+            // a due date means the end of that day
+            match parse_time(&due.replace('-', ""), db.tz()) {
+                Some(due) => db.pj_set_deadline(id, Some(due + 24 * 60 * 60 - 60)).unwrap(),
+                None => report.reject(&task.name, "the due date is not a valid date"),
+            }
+        }
+        if task.done {
+            let quota = db.pj_get_by_id(id).unwrap().quota_esti().max(1);
+            db.pj_set_quota_done(id, quota).unwrap();
+        }
+    }
+    report
+}
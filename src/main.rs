@@ -4,6 +4,8 @@ use data::DataBase;
 
 mod app;
 mod data;
+mod config;
+mod interop;
 
 #[cfg(debug_assertions)]
 const PATH: &str = "./tmp"; 
@@ -37,9 +39,26 @@ fn app_load_or_new() -> Result<app::App, Box<dyn std::error::Error>> {
     else { Ok(app::App::new()) }
 }
 
+// tman report [--format md|csv] [--root NAME] [--depth N] [--from YYYY/MM/DD] [--to YYYY/MM/DD]
+fn report(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    use interop::report::*;
+    let db = data::DataBase::load_yaml(&format!("{PATH}/data.yaml"))?;
+    let opt = ReportOpt::from_args(args, &db)?;
+    print!("{}", render(&db, &opt)?);
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(|s| s.as_str()) {
+        Some("report") => return report(&args[1..]),
+        Some(cmd) => Err(format!("unknown subcommand {cmd}"))?,
+        None => {}
+    }
     let mut db = db_load_or_new()?;
     let mut app = app_load_or_new()?;
+    app.load_config(&format!("{PATH}/config.yaml"));
+    app.load_history(&format!("{PATH}/history.yaml"));
     app.run(&mut db)?;
     app.save_yaml(&format!("{PATH}/app.yaml"))?;
     db.save_yaml(&format!("{PATH}/data.yaml"))?;